@@ -1,5 +1,6 @@
 use std::io;
 use std::io::{Read};
+use aoc2023::parse::sections;
 
 fn main() -> io::Result<()> {
 	// just buffer the whole input file at once
@@ -7,14 +8,14 @@ fn main() -> io::Result<()> {
 	let filesize = io::stdin().read_to_string(&mut buf)?;
 	println!("Read {} bytes.", filesize);
 
-	// vectorize each passport into a string slice
-	let passports = buf
-		.split("\n\n")
-		.collect::<Vec<&str>>();
+	// vectorize each passport into a string slice (with its fields on one line)
+	let passports = sections(&buf)
+		.map(|lines| lines.join(" "))
+		.collect::<Vec<String>>();
 
 	// filter out the passports without all required fields
 	let complete_passports = passports.into_iter()
-	                                  .filter(|x| has_required_fields(*x))
+	                                  .filter(|x| has_required_fields(x))
 	                                  .collect::<Vec<_>>();
 
 	println!("Found {} passports with all required fields.",
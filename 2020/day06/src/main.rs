@@ -1,6 +1,7 @@
 use std::io;
 use std::io::Read;
 use std::collections::HashMap;
+use aoc2023::parse::sections;
 
 fn main() -> io::Result<()> {
 	// just buffer the whole input file at once
@@ -8,30 +9,23 @@ fn main() -> io::Result<()> {
 	let filesize = io::stdin().read_to_string(&mut buf)?;
 	println!("Read {} bytes.", filesize);
 
-	// vectorize each answer group into a string slice
-	let answers: Vec<&str> = buf.split("\n\n").collect();
+	// vectorize each answer group into its lines (one per person)
+	let answers: Vec<Vec<&str>> = sections(&buf).collect();
 
 	// get a count for each unique character in each vector group
 	let any = answers
 		.iter()
 		.map(|group| {
-			// empty groups are have one unique character
-			if group.is_empty() {
-				0
-			} else {
-				// filter out non-answer characters
-				let mut chars: Vec<char> = group.chars()
-					.filter(|x| x.is_alphabetic() )
-					.collect();
-				// filter out non-unique characters
-				chars.sort();
-
-				// by removing duplicate adjacents and counting
-				chars.windows(2)
-					.filter(|ch| ch[0] != ch[1])
-					.count()
-					+ 1 // plus the last item (which was unchecked)
-			}
+			// filter out non-answer characters
+			let mut chars: Vec<char> = group.iter()
+				.flat_map(|person| person.chars())
+				.filter(|x| x.is_alphabetic() )
+				.collect();
+			// filter out non-unique characters
+			chars.sort();
+			chars.dedup();
+
+			chars.len()
 		});
 
 	println!("Sum of any-Yes Answers: {}", any.sum::<usize>());
@@ -41,10 +35,11 @@ fn main() -> io::Result<()> {
 		.iter()
 		.map(|group| {
 			// get the size of the answering group
-			let group_size = group.split('\n').count();
+			let group_size = group.len();
 
 			// filter out non-answer characters
-			let chars = group.chars()
+			let chars = group.iter()
+				.flat_map(|person| person.chars())
 				.filter(|x| x.is_alphabetic());
 
 			// aggregate totals for each group's answers as a map from key to
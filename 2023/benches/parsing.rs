@@ -0,0 +1,57 @@
+//! Compares ways of pulling the numbers out of puzzle inputs.
+//!
+//! Run with `cargo bench`; each approach reports the average time it takes per line.
+
+use aoc2023::{parse, regex, Input};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// How long to keep re-running each approach for.
+const RUN_FOR: Duration = Duration::from_millis(500);
+
+/// Runs `parse` over every line in `lines` until [`RUN_FOR`] has passed, then prints the average
+/// time per line.
+fn bench<F: Fn(&str) -> i64>(name: &str, lines: &[&str], parse: F) {
+    let start = Instant::now();
+    let mut parsed = 0u32;
+    while start.elapsed() < RUN_FOR {
+        for line in lines {
+            black_box(parse(black_box(line)));
+        }
+        parsed += lines.len() as u32;
+    }
+    println!("{name:>32}: {:>8.1?}/line", start.elapsed() / parsed);
+}
+
+fn main() {
+    for file in ["inputs/day05/input", "inputs/day09/input"] {
+        let input = Input::open(file).unwrap();
+        let lines = input.lines().collect::<Vec<_>>();
+        println!("{file} ({} lines)", lines.len());
+
+        // the slow way, on purpose
+        #[allow(clippy::regex_creation_in_loops)]
+        bench("Regex::new per line", &lines, |line| {
+            let numbers = parse::Regex::new("-?[0-9]+").unwrap();
+            numbers.find_iter(line).map(|n| n.as_str().parse::<i64>().unwrap()).sum()
+        });
+        bench("regex! (compiled once)", &lines, |line| {
+            regex!("-?[0-9]+").find_iter(line).map(|n| n.as_str().parse::<i64>().unwrap()).sum()
+        });
+        bench("split_whitespace + str::parse", &lines, |line| {
+            line.split_whitespace().filter_map(|n| n.parse::<i64>().ok()).sum()
+        });
+        bench("parse::ints", &lines, |line| {
+            parse::ints::<i64>(line).map(Result::unwrap).sum()
+        });
+    }
+
+    let input = Input::open("inputs/day13/input").unwrap();
+    let start = Instant::now();
+    let mut runs = 0u32;
+    while start.elapsed() < RUN_FOR {
+        black_box(parse::sections(black_box(input.as_str())).count());
+        runs += 1;
+    }
+    println!("inputs/day13/input\n{:>32}: {:>8.1?}/file", "parse::sections", start.elapsed() / runs);
+}
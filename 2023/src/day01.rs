@@ -13,6 +13,8 @@
 
 #![cfg(not(doctest))]
 
+use std::collections::VecDeque;
+
 use super::*;
 
 /// # Reading Digits
@@ -35,12 +37,11 @@ use super::*;
 /// these together produces 142.
 ///
 /// Consider your entire calibration document. What is the sum of all of the calibration values?
-pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
+pub fn solve_part1(input: &Input) -> AdvInt {
     info!("Solving (Part 1)...");
     let mut sum = 0;
 
-    for line in input {
-        let line = line.as_bytes();
+    for line in input.byte_lines() {
         let first = line.iter().find(|c| c.is_ascii_digit());
         let last = line.iter().rfind(|c| c.is_ascii_digit());
         match (first, last) {
@@ -53,7 +54,7 @@ pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
             _ => {
                 error!(
                     "Failed to find digits in {:?}",
-                    String::from_utf8(line.into())
+                    std::str::from_utf8(line)
                 );
             }
         }
@@ -85,13 +86,23 @@ pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
 /// together produces 281.
 ///
 /// What is the sum of all of the calibration values?
-pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
+pub fn solve_part2(input: &Input) -> AdvInt {
     info!("Solving (Part 2)...");
+    solve_with(input, &Scanner::new(&Vocabulary::english()))
+}
+
+/// Like part 2, but with the digits plus the words in `vocabulary` (see [`Vocabulary::parse`]).
+pub fn solve_with_vocabulary(input: &Input, vocabulary: &Input) -> Result<AdvInt, String> {
+    let words = Vocabulary::parse(vocabulary.lines())?;
+    Ok(solve_with(input, &Scanner::new(&Vocabulary::digits().with(&words))))
+}
+
+fn solve_with(input: &Input, scanner: &Scanner) -> AdvInt {
     let mut sum = 0usize;
 
-    for line in input {
-        let first = find_first_numeral(&line);
-        let last = find_last_numeral(&line);
+    for line in input.lines() {
+        let first = scanner.first(line);
+        let last = scanner.last(line);
         match (first, last) {
             (Some(first), Some(last)) => {
                 let value = first.val * 10 + last.val;
@@ -100,10 +111,7 @@ pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
                 debug!("Running sum: {sum}");
             }
             _ => {
-                error!(
-                    "Failed to find digits in {:?}",
-                    String::from_utf8(line.into())
-                );
+                error!("Failed to find digits in {line:?}");
             }
         }
     }
@@ -113,26 +121,43 @@ pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
 
 type AdvInt = usize;
 
-const PAIRS: [(usize, &str); 18] = [
-    (1, "one"),
-    (1, "1"),
-    (2, "two"),
-    (2, "2"),
-    (3, "three"),
-    (3, "3"),
-    (4, "four"),
-    (4, "4"),
-    (5, "five"),
-    (5, "5"),
-    (6, "six"),
-    (6, "6"),
-    (7, "seven"),
-    (7, "7"),
-    (8, "eight"),
-    (8, "8"),
-    (9, "nine"),
-    (9, "9"),
-];
+const ENGLISH: &str = include_str!("../inputs/day01/english");
+
+/// The words which count as numerals, and their values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Vocabulary(pub Vec<(String, usize)>);
+
+impl Vocabulary {
+    /// Reads one `word = value` per line, skipping blank lines.
+    pub fn parse<'a, L: IntoIterator<Item = &'a str>>(lines: L) -> Result<Self, String> {
+        let mut words = vec![];
+        for (i, line) in lines.into_iter().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let (word, value) = line.split_once('=').ok_or(format!("line {}: expected `word = value`", i + 1))?;
+            let value = value.trim().parse().map_err(|_| format!("line {}: bad value: {value}", i + 1))?;
+            let word = word.trim();
+            if word.is_empty() {
+                return Err(format!("line {}: missing word", i + 1));
+            }
+            words.push((word.to_string(), value));
+        }
+        Ok(Self(words))
+    }
+
+    /// Just the digits 1 through 9.
+    pub fn digits() -> Self {
+        Self((1..=9).map(|d| (d.to_string(), d)).collect())
+    }
+
+    /// The digits, and the English words for them.
+    pub fn english() -> Self {
+        Self::digits().with(&Self::parse(ENGLISH.lines()).unwrap())
+    }
+
+    /// Both vocabularies together.
+    pub fn with(&self, other: &Vocabulary) -> Self {
+        Self([self.0.clone(), other.0.clone()].concat())
+    }
+}
 
 struct Finding {
     /// The index where a digit (ascii or numeral) is found in some string
@@ -141,65 +166,164 @@ struct Finding {
     val: usize,
 }
 
-fn find_first_numeral(line: &String) -> Option<Finding> {
-    let mut numeral: Option<Finding> = None;
-
-    debug!("FORWARD SEARCH of '{line}'");
-    for (textvalue, text) in PAIRS {
-        if let Some(position) = line.find(text) {
-            match numeral {
-                None => {
-                    debug!("Found text '{text:?}' at {position} (First found)");
-                    numeral = Some(Finding {
-                        idx: position,
-                        val: textvalue,
-                    });
+/// Finds the first and last numerals in a line, each in a single pass.
+///
+/// The first numeral is the one which starts earliest and the last is the one which ends latest
+/// (taking the longest word on a tie), so overlapping words like "eightwo" give 8 and then 2.
+pub struct Scanner {
+    forward: Automaton,
+    /// matches the words spelled backwards, for scanning lines from the end
+    reverse: Automaton,
+}
+
+impl Scanner {
+    pub fn new(vocabulary: &Vocabulary) -> Self {
+        let reversed = vocabulary.0.iter()
+            .map(|(word, value)| (word.bytes().rev().collect(), *value))
+            .collect::<Vec<_>>();
+        let forward = vocabulary.0.iter()
+            .map(|(word, value)| (word.bytes().collect(), *value))
+            .collect::<Vec<_>>();
+        Self {
+            forward: Automaton::new(&forward),
+            reverse: Automaton::new(&reversed),
+        }
+    }
+
+    fn first(&self, line: &str) -> Option<Finding> {
+        debug!("FORWARD SEARCH of '{line}'");
+        self.forward.leftmost(line.bytes())
+            .map(|(idx, _, val)| Finding { idx, val })
+            .inspect(|found| debug!("Found {} at {}", found.val, found.idx))
+    }
+
+    fn last(&self, line: &str) -> Option<Finding> {
+        debug!("REVERSE SEARCH of '{line}'");
+        self.reverse.leftmost(line.bytes().rev())
+            .map(|(idx, len, val)| Finding { idx: line.len() - idx - len, val })
+            .inspect(|found| debug!("Found {} at {}", found.val, found.idx))
+    }
+}
+
+/// An Aho-Corasick automaton over bytes, with every transition filled in (so each byte scanned
+/// is a single lookup).
+struct Automaton {
+    delta: Vec<[usize; 256]>,
+    /// how many bytes deep in the trie each state is
+    depth: Vec<usize>,
+    /// the value of the word ending at each state, if any
+    value: Vec<Option<usize>>,
+    /// the next state down the failure links which ends a word
+    dict: Vec<Option<usize>>,
+}
+
+impl Automaton {
+    fn new(words: &[(Vec<u8>, usize)]) -> Self {
+        const NONE: usize = usize::MAX;
+        let mut auto = Automaton { delta: vec![[NONE; 256]], depth: vec![0], value: vec![None], dict: vec![None] };
+
+        // build the trie, keeping the first value for any repeated word
+        for (word, val) in words {
+            let mut state = 0;
+            for &b in word {
+                if auto.delta[state][b as usize] == NONE {
+                    auto.delta.push([NONE; 256]);
+                    auto.depth.push(auto.depth[state] + 1);
+                    auto.value.push(None);
+                    auto.dict.push(None);
+                    auto.delta[state][b as usize] = auto.delta.len() - 1;
                 }
-                Some(p) if p.idx > position => {
-                    debug!("Found text '{text:?}' at {position}");
-                    numeral = Some(Finding {
-                        idx: position,
-                        val: textvalue,
-                    });
+                state = auto.delta[state][b as usize];
+            }
+            auto.value[state] = auto.value[state].or(Some(*val));
+        }
+
+        // then fill in the rest of the transitions breadth-first, from each state's failure link
+        let mut fail = vec![0; auto.delta.len()];
+        let mut queue = VecDeque::from([0]);
+        while let Some(state) = queue.pop_front() {
+            for b in 0..256 {
+                let next = auto.delta[state][b];
+                let fallback = match state {
+                    0 => 0,
+                    _ => auto.delta[fail[state]][b],
+                };
+                if next == NONE {
+                    auto.delta[state][b] = fallback;
+                } else {
+                    fail[next] = fallback;
+                    auto.dict[next] = match auto.value[fallback] {
+                        Some(_) => Some(fallback),
+                        None => auto.dict[fallback],
+                    };
+                    queue.push_back(next);
                 }
-                _ => {}
             }
         }
+
+        auto
     }
 
-    numeral
-}
+    /// Finds the match which starts first (and is longest, for a tie), as its start, length and
+    /// value. Scanning stops as soon as no earlier match could still turn up.
+    fn leftmost<I: Iterator<Item = u8>>(&self, bytes: I) -> Option<(usize, usize, usize)> {
+        let mut best: Option<(usize, usize, usize)> = None;
+        let mut state = 0;
 
-fn find_last_numeral(line: &String) -> Option<Finding> {
-    let mut numeral: Option<Finding> = None;
-
-    debug!("REVERSE SEARCH of '{line}'");
-    for (textvalue, text) in PAIRS {
-        if let Some(position) = line.rfind(text) {
-            match numeral {
-                None => {
-                    debug!("Found text '{text:?}' at {position} (First found)");
-                    numeral = Some(Finding {
-                        idx: position,
-                        val: textvalue,
-                    });
+        for (pos, b) in bytes.enumerate() {
+            state = self.delta[state][b as usize];
+            let mut found = match self.value[state] {
+                Some(_) => Some(state),
+                None => self.dict[state],
+            };
+            while let Some(s) = found {
+                let (len, val) = (self.depth[s], self.value[s].unwrap());
+                let start = pos + 1 - len;
+                if best.is_none_or(|(b_start, b_len, _)| start < b_start || (start == b_start && len > b_len)) {
+                    best = Some((start, len, val));
                 }
-                Some(p) if p.idx < position => {
-                    debug!("Found text '{text:?}' at {position}");
-                    numeral = Some(Finding {
-                        idx: position,
-                        val: textvalue,
-                    });
-                }
-                _ => {}
+                found = self.dict[s];
+            }
+
+            // any match still to come starts within the current state's depth
+            if best.is_some_and(|(b_start, _, _)| pos + 1 - self.depth[state] > b_start) {
+                break;
             }
         }
+
+        best
+    }
+}
+
+#[test]
+fn overlapping_words() {
+    log_init();
+    let scanner = Scanner::new(&Vocabulary::english());
+    for (line, first, last) in [("eightwo", 8, 2), ("twone", 2, 1), ("oneight", 1, 8), ("xx7", 7, 7), ("sevenine", 7, 9)] {
+        assert_eq!(scanner.first(line).map(|f| f.val), Some(first), "{line}");
+        assert_eq!(scanner.last(line).map(|f| f.val), Some(last), "{line}");
     }
+    assert!(scanner.first("abc").is_none());
+
+    // the longest word wins when several start at once
+    let roman = Scanner::new(&Vocabulary::parse(get_input(get_day!(), "roman").lines()).unwrap());
+    assert_eq!(roman.first("xviii").map(|f| (f.idx, f.val)), Some((1, 8)));
+    assert_eq!(roman.last("xviii").map(|f| (f.idx, f.val)), Some((1, 8)));
+    assert_eq!(roman.last("viv").map(|f| (f.idx, f.val)), Some((1, 4)));
+
+    // a word inside a longer one starting earlier
+    let nested = Scanner::new(&Vocabulary::parse(["abcd = 1", "bc = 2"]).unwrap());
+    assert_eq!(nested.first("xabcd").map(|f| f.val), Some(1));
+    assert_eq!(nested.last("abcdx").map(|f| f.val), Some(1));
+    assert_eq!(nested.last("abcbc").map(|f| f.val), Some(2));
 
-    numeral
+    assert!(Vocabulary::parse(["one 1"]).is_err());
+    assert!(Vocabulary::parse(["one = I"]).is_err());
 }
 
 testcase!(ex1, solve_part1, "example", 142);
 testcase!(part1, solve_part1, "input", 53194);
 testcase!(ex2, solve_part2, "example2", 281);
 testcase!(part2, solve_part2, "input", 54249);
+testcase!(english, solve_with_vocabulary, "input", Ok(54249), &get_input(get_day!(), "english"));
+testcase!(german, solve_with_vocabulary, "example3", Ok(12 + 37 + 89 + 57), &get_input(get_day!(), "german"));
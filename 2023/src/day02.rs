@@ -15,8 +15,9 @@
 
 #![cfg(not(doctest))]
 
+use std::collections::{BTreeMap, BTreeSet};
+
 use super::*;
-use regex::Regex;
 
 /// # Possible Games
 ///
@@ -53,21 +54,16 @@ use regex::Regex;
 ///
 /// Determine which games would have been possible if the bag had been loaded with only 12 red
 /// cubes, 13 green cubes, and 14 blue cubes. What is the sum of the IDs of those games?
-pub fn solve_part1<L: IntoIterator<Item = String>>(input: L, red: usize, green: usize, blue: usize) -> AdvInt {
+pub fn solve_part1(input: &Input, red: usize, green: usize, blue: usize) -> AdvInt {
     info!("Solving part 1...");
-    let mut sum_ids = 0;
-
-    for line in input {
-        debug!("got line: {}", line);
-        let game = Game::from_line(line).unwrap();
-        info!("resulting game: {game:?}");
-        if game.playable_with(red, green, blue) {
-            info!("Can play game {}", game.id);
-            sum_ids += game.id;
-        }
-    }
+    let bag = Cubes::from([("red", red), ("green", green), ("blue", blue)]);
+    sum_playable(input, &bag).unwrap()
+}
 
-    sum_ids
+/// Sums the IDs of the games which could have been played with `bag` (written like a round, e.g.
+/// `12 red, 13 green, 14 blue`).
+pub fn solve_with_bag(input: &Input, bag: &str) -> Result<AdvInt, String> {
+    sum_playable(input, &bag.parse()?)
 }
 
 /// # Powers of Minimums
@@ -107,81 +103,252 @@ pub fn solve_part1<L: IntoIterator<Item = String>>(input: L, red: usize, green:
 ///
 /// For each game, find the minimum set of cubes that must have been present. What is the sum of
 /// the power of these sets?
-pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
+pub fn solve_part2(input: &Input) -> AdvInt {
     info!("Solving part 2...");
-    let mut sum_powers = 0;
+    solve_powers(input, &["red", "green", "blue"]).unwrap()
+}
 
-    for line in input {
-        debug!("got line: {}", line);
-        let game = Game::from_line(line).unwrap();
-        info!("resulting game: {game:?}");
-        sum_powers += game.power();
+/// Sums the power of each game's minimum set of cubes, counting only the given `colours`.
+pub fn solve_powers(input: &Input, colours: &[&str]) -> Result<AdvInt, String> {
+    Ok(parse_games(input)?
+        .iter()
+        .map(|game| game.minimal_bag().power(colours))
+        .sum())
+}
+
+/// Lists every smallest bag which could have produced all of the games, written like rounds.
+///
+/// Without a `total`, that's just the most cubes of each colour seen at once (any bag missing one
+/// of those couldn't have been used, and any other bag holds more). If the bag is known to have
+/// held `total` cubes, the rest could be any of the colours seen, so every such split is listed.
+pub fn infer_bags(input: &Input, total: Option<usize>) -> Result<Vec<String>, String> {
+    let games = parse_games(input)?;
+    let smallest = games.iter()
+        .map(Game::minimal_bag)
+        .fold(Cubes::default(), |bag, other| bag.union(&other));
+    let Some(total) = total else {
+        return Ok(vec![smallest.to_string()]);
+    };
+
+    let spare = total.checked_sub(smallest.size())
+        .ok_or(format!("the games need at least {} cubes", smallest.size()))?;
+    let colours = smallest.0.keys().cloned().collect::<Vec<_>>();
+    if colours.is_empty() && spare > 0 {
+        return Err("no colours to fill the bag with".into());
     }
 
-    sum_powers
+    let mut bags = vec![];
+    let mut extra = vec![0; colours.len()];
+    spread(&mut extra, 0, spare, &mut |extra| {
+        let mut bag = smallest.clone();
+        for (colour, &n) in colours.iter().zip(extra) {
+            *bag.0.get_mut(colour).unwrap() += n;
+        }
+        bags.push(bag.to_string());
+    });
+    Ok(bags)
+}
+
+/// Calls `visit` with every way to split `left` between `extra[i..]`.
+fn spread<F: FnMut(&[usize])>(extra: &mut [usize], i: usize, left: usize, visit: &mut F) {
+    if i + 1 >= extra.len() {
+        if let Some(last) = extra.last_mut() {
+            *last = left;
+        }
+        visit(extra);
+        return;
+    }
+    for n in (0..=left).rev() {
+        extra[i] = n;
+        spread(extra, i + 1, left - n, visit);
+    }
 }
 
 type AdvInt = usize;
 
-#[derive(Debug, Clone)]
-struct Game {
-    id: usize,
-    reds: usize,
-    blues: usize,
-    greens: usize,
+/// Sums the IDs of the games which could have been played with `bag`.
+fn sum_playable(input: &Input, bag: &Cubes) -> Result<AdvInt, String> {
+    let mut sum_ids = 0;
+
+    for game in parse_games(input)? {
+        info!("resulting game: {game:?}");
+        if game.playable_with(bag) {
+            info!("Can play game {}", game.id);
+            sum_ids += game.id;
+        }
+    }
+
+    Ok(sum_ids)
 }
 
-impl Game {
-    pub fn from_line<L: AsRef<str>>(line: L) -> Option<Self> {
-        let line = line.as_ref();
+fn parse_games(input: &Input) -> Result<Vec<Game>, String> {
+    input.lines()
+        .inspect(|line| debug!("got line: {line}"))
+        .map(|line| line.parse())
+        .collect()
+}
+
+/// A handful of cubes (or a whole bag of them), by colour.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cubes(pub BTreeMap<String, usize>);
 
-        let game_re = Regex::new("Game ([0-9]+):").unwrap();
-        let red_re = Regex::new("([0-9]+) red").unwrap();
-        let green_re = Regex::new("([0-9]+) green").unwrap();
-        let blue_re = Regex::new("([0-9]+) blue").unwrap();
+impl Cubes {
+    /// How many cubes of `colour` there are.
+    pub fn get(&self, colour: &str) -> usize {
+        self.0.get(colour).copied().unwrap_or(0)
+    }
+
+    /// How many cubes there are altogether.
+    pub fn size(&self) -> usize {
+        self.0.values().sum()
+    }
+
+    /// Whether these cubes could all have come out of `bag` at once.
+    pub fn fits_in(&self, bag: &Cubes) -> bool {
+        self.0.iter().all(|(colour, &n)| n <= bag.get(colour))
+    }
+
+    /// The most of each colour between these cubes and `other`.
+    pub fn union(&self, other: &Cubes) -> Cubes {
+        let mut union = self.clone();
+        for (colour, &n) in &other.0 {
+            let count = union.0.entry(colour.clone()).or_default();
+            *count = n.max(*count);
+        }
+        union
+    }
+
+    /// The counts of each of `colours` multiplied together.
+    pub fn power(&self, colours: &[&str]) -> usize {
+        colours.iter().map(|c| self.get(c)).product()
+    }
+}
+
+impl<const N: usize> From<[(&str, usize); N]> for Cubes {
+    fn from(counts: [(&str, usize); N]) -> Self {
+        Self(counts.iter().map(|&(colour, n)| (colour.to_string(), n)).collect())
+    }
+}
+
+impl std::str::FromStr for Cubes {
+    type Err = String;
+
+    /// Parses a round like `3 blue, 4 red`, where the colours can be any word. Colours which show
+    /// up more than once are added together.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cubes = BTreeMap::new();
+        for grab in s.split(',').map(str::trim).filter(|g| !g.is_empty()) {
+            let (n, colour) = grab.split_once(' ').ok_or(format!("expected `count colour`: {grab}"))?;
+            let n = n.parse::<usize>().map_err(|_| format!("bad count: {grab}"))?;
+            *cubes.entry(colour.trim().to_string()).or_default() += n;
+        }
+        Ok(Self(cubes))
+    }
+}
+
+impl std::fmt::Display for Cubes {
+    /// Writes the cubes out like a round, e.g. `4 red, 3 blue`.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let grabs = self.0.iter()
+            .map(|(colour, n)| format!("{n} {colour}"))
+            .collect::<Vec<_>>();
+        write!(f, "{}", grabs.join(", "))
+    }
+}
+
+/// Every round of cubes the Elf showed you in one game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub id: usize,
+    pub rounds: Vec<Cubes>,
+}
 
-        let game = game_re.captures(line).unwrap().get(1).unwrap();
-        let id = game.as_str().parse::<usize>().ok().unwrap();
+impl std::str::FromStr for Game {
+    type Err = String;
 
-        debug!("found: {id}");
-        let (_, grabs) = line.split_at(game.end());
+    /// Parses a line like `Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green`.
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let (game, rounds) = line.split_once(':').ok_or(format!("expected `Game N:`: {line}"))?;
+        let id = game.strip_prefix("Game ")
+            .and_then(|id| id.trim().parse().ok())
+            .ok_or(format!("bad game id: {game}"))?;
+        let rounds = rounds.split(';')
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { id, rounds })
+    }
+}
 
-        let reds = red_re.captures_iter(grabs)
-            .fold(0, |acc, cap| {
-                let (_, [c]) = cap.extract();
-                debug!("red cap: {}", c);
-                acc.max(c.parse::<usize>().unwrap())
-            });
+impl Game {
+    /// Every colour shown in any round.
+    pub fn colours(&self) -> BTreeSet<&str> {
+        self.rounds.iter()
+            .flat_map(|round| round.0.keys().map(String::as_str))
+            .collect()
+    }
 
-        let greens = green_re.captures_iter(grabs)
-            .fold(0, |acc, cap| {
-                let (_, [c]) = cap.extract();
-                debug!("green cap: {}", c);
-                acc.max(c.parse::<usize>().unwrap())
-            });
+    /// The most cubes of `colour` shown in a single round.
+    pub fn max(&self, colour: &str) -> usize {
+        self.rounds.iter().map(|round| round.get(colour)).max().unwrap_or(0)
+    }
 
-        let blues = blue_re.captures_iter(grabs)
-            .fold(0, |acc, cap| {
-                let (_, [c]) = cap.extract();
-                debug!("blue cap: {}", c);
-                acc.max(c.parse::<usize>().unwrap())
-            });
+    /// How many cubes of `colour` were shown over the whole game.
+    pub fn total(&self, colour: &str) -> usize {
+        self.rounds.iter().map(|round| round.get(colour)).sum()
+    }
 
-        Some(Self { id, reds, greens, blues })
+    /// The rounds (numbered from 1) which match `pred`.
+    pub fn rounds_where<P: Fn(&Cubes) -> bool>(&self, pred: P) -> impl Iterator<Item = (usize, &Cubes)> {
+        self.rounds.iter()
+            .enumerate()
+            .filter(move |(_, round)| pred(round))
+            .map(|(i, round)| (i + 1, round))
     }
 
-    pub fn playable_with(&self, reds: usize, greens: usize, blues: usize) -> bool {
-        debug!("CHECKING: {:?} vs {reds}, {greens}, {blues}", self);
-        (reds >= self.reds) && (greens >= self.greens) && (blues >= self.blues)
+    /// The fewest cubes of each colour the bag could have held.
+    pub fn minimal_bag(&self) -> Cubes {
+        self.rounds.iter().fold(Cubes::default(), |bag, round| bag.union(round))
     }
 
-    pub fn power(&self) -> usize {
-        self.reds * self.greens * self.blues
+    pub fn playable_with(&self, bag: &Cubes) -> bool {
+        debug!("CHECKING: {:?} vs {bag}", self);
+        self.rounds.iter().all(|round| round.fits_in(bag))
     }
 }
 
+#[test]
+fn rounds() {
+    log_init();
+    let game = "Game 7: 3 blue, 4 red; 1 red, 2 green, 6 blue, 1 red; 2 mauve".parse::<Game>().unwrap();
+    assert_eq!(game.id, 7);
+    assert_eq!(game.rounds.len(), 3);
+    assert_eq!(game.colours(), BTreeSet::from(["blue", "green", "mauve", "red"]));
+    assert_eq!(game.max("red"), 4);
+    assert_eq!(game.total("red"), 6);
+    assert_eq!(game.max("orange"), 0);
+    assert_eq!(game.rounds_where(|r| r.size() > 7).map(|(i, _)| i).collect::<Vec<_>>(), [2]);
+    assert_eq!(game.minimal_bag().to_string(), "6 blue, 2 green, 2 mauve, 4 red");
+    assert!(game.playable_with(&"4 red, 6 blue, 2 green, 2 mauve".parse().unwrap()));
+    assert!(!game.playable_with(&"4 red, 6 blue, 2 green".parse().unwrap()));
+
+    assert!("Game 1 3 blue".parse::<Game>().is_err());
+    assert!("Game one: 3 blue".parse::<Game>().is_err());
+    assert!("Game 1: blue".parse::<Game>().is_err());
+}
 
 testcase!(ex1, solve_part1, "example", 8, 12, 13, 14);
 testcase!(part1, solve_part1, "input", 2541, 12, 13, 14);
 testcase!(ex2, solve_part2, "example", 2286);
 testcase!(part2, solve_part2, "input", 66016);
+testcase!(bag_ex, solve_with_bag, "example", Ok(1 + 2 + 4 + 5), "14 red, 3 green, 15 blue");
+testcase!(power_ex, solve_powers, "example", Ok(4 + 1 + 20 + 14 + 6), &["red"]);
+testcase!(infer_ex, infer_bags, "example", Ok(vec!["15 blue, 13 green, 20 red".into()]), None);
+testcase!(infer_total_ex, infer_bags, "example", Ok(vec![
+    "17 blue, 13 green, 20 red".into(),
+    "16 blue, 14 green, 20 red".into(),
+    "16 blue, 13 green, 21 red".into(),
+    "15 blue, 15 green, 20 red".into(),
+    "15 blue, 14 green, 21 red".into(),
+    "15 blue, 13 green, 22 red".into(),
+]), Some(50));
+testcase!(infer_small, infer_bags, "example", Err("the games need at least 48 cubes".into()), Some(47));
@@ -15,8 +15,7 @@
 #![cfg(not(doctest))]
 
 use super::*;
-use std::{collections::HashMap, iter::repeat};
-use regex::bytes::Regex;
+use std::collections::HashMap;
 
 /// # Just the Parts
 ///
@@ -50,13 +49,8 @@ use regex::bytes::Regex;
 ///
 /// Of course, the actual engine schematic is much larger. What is the sum of all of the part
 /// numbers in the engine schematic?
-pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let sch = Schematic::from_lines(input);
-    let sum = sch.all_nums.iter()
-        .filter(|&num| num.near_symbol)
-        .map(|num| num.value)
-        .sum();
-    sum
+pub fn solve_part1(input: &Input) -> AdvInt {
+    solve_numbers(input, None, Aggregate::Sum)
 }
 
 /// # Product of Gear Ratios
@@ -99,110 +93,178 @@ pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
 /// Adding up all of the gear ratios produces 467835.
 ///
 /// What is the sum of all of the gear ratios in your engine schematic?
-pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let sch = Schematic::from_lines(input);
-    let sum = sch.gears.values()
-        .filter(|g| g.nums.len() == 2)
-        .map(|g| g.nums[0].value * g.nums[1].value)
-        .sum();
-    sum
+pub fn solve_part2(input: &Input) -> AdvInt {
+    solve_symbols(input, Some('*'), 2, Aggregate::Product, Aggregate::Sum)
+}
+
+/// Combines the numbers which touch a symbol of the given `kind` (or any symbol, if `None`).
+pub fn solve_numbers(input: &Input, kind: Option<char>, total: Aggregate) -> AdvInt {
+    let sch = Schematic::from_lines(input.lines());
+    total.apply(sch.numbers_touching(kind).map(|num| num.value))
+}
+
+/// Finds each symbol of the given `kind` (or of any kind, if `None`) which touches exactly
+/// `count` numbers, combines each of their numbers with `per_symbol`, then combines those results
+/// with `total`.
+pub fn solve_symbols(input: &Input, kind: Option<char>, count: usize, per_symbol: Aggregate, total: Aggregate) -> AdvInt {
+    let sch = Schematic::from_lines(input.lines());
+    total.apply(sch.symbols_touching(kind, count)
+        .map(|(s, _)| per_symbol.apply(sch.numbers_around(s).map(|num| num.value))))
 }
 
 type AdvInt = usize;
 
-#[derive(Default, Debug)]
-struct Schematic {
-    flat_map: Vec<u8>,
-    width: usize,
-    height: usize,
-    all_nums: Vec<Number>,
-    gears: HashMap<Coordinate, Gear>,
+/// How to combine a handful of numbers into one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Product,
+    /// the largest, or 0 if there are none
+    Max,
+    Count,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
-struct Coordinate {
-    x: isize,
-    y: isize,
+impl Aggregate {
+    pub fn apply<I: IntoIterator<Item = AdvInt>>(&self, values: I) -> AdvInt {
+        let values = values.into_iter();
+        match self {
+            Aggregate::Sum => values.sum(),
+            Aggregate::Product => values.product(),
+            Aggregate::Max => values.max().unwrap_or(0),
+            Aggregate::Count => values.count(),
+        }
+    }
 }
 
-#[derive(Debug, Copy, Clone)]
-struct Number {
-    value: usize,
-    near_symbol: bool,
+/// Every number and symbol in the engine schematic, along with which of them touch.
+#[derive(Default, Debug)]
+pub struct Schematic {
+    pub numbers: Vec<Number>,
+    pub symbols: Vec<Symbol>,
+    /// the symbols (by index) around each number
+    number_edges: Vec<Vec<usize>>,
+    /// the numbers (by index) around each symbol
+    symbol_edges: Vec<Vec<usize>>,
 }
 
-#[derive(Debug, Default)]
-struct Gear {
-    nums: Vec<Number>,
+/// A number, which starts at (`x`, `y`) and spans `len` digits to the right.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Number {
+    pub value: usize,
+    pub x: usize,
+    pub y: usize,
+    pub len: usize,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub kind: char,
+    pub x: usize,
+    pub y: usize,
+}
 
 impl Schematic {
-    pub fn from_lines<L: IntoIterator<Item = String>>(lines: L) -> Self {
+    pub fn from_lines<'a, L: IntoIterator<Item = &'a str>>(lines: L) -> Self {
         let mut schematic = Schematic::default();
-        for line in lines {
-            let line = line.as_bytes().to_vec();
-            schematic.width = schematic.width.max(line.len());
-            schematic.height += 1;
-            schematic.flat_map = [schematic.flat_map, line].concat();
-        }
+        let mut symbol_at = HashMap::new();
 
-        // verify that our computed dimensions represent our data as a rectangle
-        debug_assert_eq!(schematic.width * schematic.height, schematic.flat_map.len());
-
-        // find each number in the file
-        let re = Regex::new("[0-9]+").unwrap();
-        for num in re.find_iter(&schematic.flat_map) {
-            // compute coordinates of the found number
-            let start_x = (num.start() % schematic.width) as isize;
-            let end_x = (num.end() % schematic.width) as isize;
-            let y = (num.start() / schematic.width) as isize;
-
-            // compute coordinates of all of its neighbors
-            let toprow = ((start_x - 1)..=(end_x)).zip(repeat(y - 1));
-            let middle = [(start_x - 1, y), (end_x, y)].into_iter();
-            let bottom = ((start_x - 1)..=(end_x)).zip(repeat(y + 1));
-            let neighbors = toprow.chain(middle).chain(bottom);
-
-            // determine whether it's next to a symbol
-            let near_symbol = neighbors.clone()
-                .any(|(x, y)| schematic.at(x, y) != b'.');
-            // and save its integral value
-            let value = String::from_utf8(num.as_bytes().to_owned())
-                .unwrap()
-                .parse::<usize>()
-                .unwrap();
-
-            // check for gears in its neighborhood
-            let number = Number { value, near_symbol};
-            let gears: Vec<_> = neighbors.filter(|(x, y)| schematic.at(*x, *y) == b'*').collect();
-            for (x, y) in gears {
-                // add it to the list of that gear's neighboring numbers (for the gear that we found)
-                schematic.gears.entry(Coordinate { x, y })
-                    .and_modify(|g| g.nums.push(number))
-                    .or_insert(Gear { nums: vec![number] });
+        // find each number and symbol in the file
+        for (y, line) in lines.into_iter().enumerate() {
+            let line = line.as_bytes();
+            let mut x = 0;
+            while x < line.len() {
+                let len = line[x..].iter().take_while(|c| c.is_ascii_digit()).count();
+                if len > 0 {
+                    let value = std::str::from_utf8(&line[x..x + len]).unwrap().parse().unwrap();
+                    schematic.numbers.push(Number { value, x, y, len });
+                    x += len;
+                    continue;
+                }
+                if line[x] != b'.' {
+                    symbol_at.insert((x, y), schematic.symbols.len());
+                    schematic.symbols.push(Symbol { kind: line[x] as char, x, y });
+                }
+                x += 1;
             }
+        }
 
-            // store the parsed number and its attributes
-            schematic.all_nums.push(number);
+        // then join each number to the symbols in its neighborhood
+        schematic.symbol_edges = vec![vec![]; schematic.symbols.len()];
+        for (n, num) in schematic.numbers.iter().enumerate() {
+            let xs = num.x.saturating_sub(1)..=num.x + num.len;
+            let ys = num.y.saturating_sub(1)..=num.y + 1;
+            let symbols = ys.flat_map(|y| xs.clone().map(move |x| (x, y)))
+                .filter_map(|pos| symbol_at.get(&pos).copied())
+                .collect::<Vec<_>>();
+            for &s in &symbols {
+                schematic.symbol_edges[s].push(n);
+            }
+            schematic.number_edges.push(symbols);
         }
 
         schematic
     }
 
-    fn at(&self, x: isize, y: isize) -> u8 {
-        // bounds check x and y (out-of-bounds = default value of b'.')
-        if x >= 0 && y >= 0 {
-            let x = x as usize;
-            let y = y as usize;
-            self.flat_map.get(x + (y * self.width)).map_or(b'.', |c| *c)
-        } else {
-            b'.'
-        }
+    /// The numbers which touch a symbol of the given `kind` (or any symbol, if `None`).
+    pub fn numbers_touching(&self, kind: Option<char>) -> impl Iterator<Item = &Number> {
+        self.numbers.iter()
+            .zip(&self.number_edges)
+            .filter(move |(_, symbols)| symbols.iter().any(|&s| kind.is_none_or(|k| self.symbols[s].kind == k)))
+            .map(|(num, _)| num)
+    }
+
+    /// The symbols (with their index) of the given `kind` (or of any kind, if `None`) which touch
+    /// exactly `count` numbers.
+    pub fn symbols_touching(&self, kind: Option<char>, count: usize) -> impl Iterator<Item = (usize, &Symbol)> {
+        self.symbols.iter()
+            .enumerate()
+            .zip(&self.symbol_edges)
+            .filter(move |((_, s), nums)| nums.len() == count && kind.is_none_or(|k| s.kind == k))
+            .map(|(s, _)| s)
+    }
+
+    /// The numbers which touch the symbol at index `s` in [`Schematic::symbols`].
+    pub fn numbers_around(&self, s: usize) -> impl Iterator<Item = &Number> {
+        self.symbol_edges[s].iter().map(|&n| &self.numbers[n])
+    }
+
+    /// The symbols which touch the number at index `n` in [`Schematic::numbers`].
+    pub fn symbols_around(&self, n: usize) -> impl Iterator<Item = &Symbol> {
+        self.number_edges[n].iter().map(|&s| &self.symbols[s])
     }
 }
 
+#[test]
+fn graph_ex() {
+    log_init();
+    let sch = Schematic::from_lines(get_input(get_day!(), "example").lines());
+    assert_eq!(sch.numbers.len(), 10);
+    assert_eq!(sch.symbols.iter().map(|s| s.kind).collect::<String>(), "*#*+$*");
+    assert_eq!(sch.numbers[0], Number { value: 467, x: 0, y: 0, len: 3 });
+
+    let values = |nums: Vec<&Number>| nums.iter().map(|n| n.value).collect::<Vec<_>>();
+    assert_eq!(values(sch.numbers_touching(Some('#')).collect()), [633]);
+    assert_eq!(values(sch.numbers_touching(None).collect()).len(), 8);
+    assert_eq!(sch.symbols_touching(Some('*'), 1).collect::<Vec<_>>(), [(2, &Symbol { kind: '*', x: 3, y: 4 })]);
+    assert_eq!(sch.symbols_touching(None, 0).count(), 0);
+    assert_eq!(values(sch.numbers_around(5).collect()), [755, 598]);
+    let kinds = sch.symbols_around(3).map(|s| s.kind).collect::<String>();
+    assert_eq!(kinds, "#");
+}
+
+#[test]
+fn numbers_at_line_ends() {
+    log_init();
+    let sch = Schematic::from_lines(["..12", "34*."]);
+    assert_eq!(sch.numbers.iter().map(|n| n.value).collect::<Vec<_>>(), [12, 34]);
+    assert_eq!(sch.numbers_touching(None).count(), 2);
+}
+
 testcase!(ex1, solve_part1, "example", 4361);
 testcase!(part1, solve_part1, "input", 556057);
 testcase!(ex2, solve_part2, "example", 467835);
 testcase!(part2, solve_part2, "input", 82824352);
+testcase!(largest_part, solve_numbers, "example", 633, Some('#'), Aggregate::Max);
+testcase!(plain_gears, solve_symbols, "example", 617, Some('*'), 1, Aggregate::Sum, Aggregate::Sum);
+testcase!(largest_ratio, solve_symbols, "example", 451490, None, 2, Aggregate::Product, Aggregate::Max);
+testcase!(gear_count, solve_symbols, "input", 331, Some('*'), 2, Aggregate::Count, Aggregate::Count);
@@ -25,8 +25,7 @@
 #![cfg(not(doctest))]
 
 use super::*;
-use std::{collections::HashSet, iter::repeat};
-use regex::Regex;
+use std::{collections::{HashSet, VecDeque}, io::Write};
 
 /// # Winning Numbers
 ///
@@ -65,28 +64,12 @@ use regex::Regex;
 /// So, in this example, the Elf's pile of scratchcards is worth 13 points.
 ///
 /// Take a seat in the large pile of colorful cards. How many points are they worth in total?
-pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let mut sum = 0;
-    let re = Regex::new("[0-9]+").unwrap();
-
-    for line in input {
-        let (_, line) = line.split_once(':').unwrap();
-        let mut line = line.split(|ch| ch == '|');
-        let (winning, ours) = (line.next().unwrap(), line.next().unwrap());
-        let winning: HashSet<usize> = re.find_iter(winning)
-            .map(|x| x.as_str().parse().unwrap())
-            .collect();
-        let ours: HashSet<usize> = re.find_iter(ours)
-            .map(|x| x.as_str().parse().unwrap())
-            .collect();
-
-        let num_matches = winning.intersection(&ours).count() as u32;
-        if num_matches > 0 {
-            sum += 2usize.pow(num_matches - 1);
-        }
-    }
-
-    sum
+pub fn solve_part1(input: &Input) -> AdvInt {
+    input.lines()
+        .map(|line| Card::parse(line).unwrap().matches)
+        .filter(|&matches| matches > 0)
+        .map(|matches| 1 << (matches - 1))
+        .sum()
 }
 
 /// # Exponential Winnings
@@ -137,41 +120,116 @@ pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
 ///
 /// Process all of the original and copied scratchcards until no more scratchcards are won.
 /// Including the original set of scratchcards, how many total scratchcards do you end up with?
-pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let mut matchlist = vec![];
-    let re = Regex::new("[0-9]+").unwrap();
-
-    for line in input {
-        let (_, line) = line.split_once(':').unwrap();
-        let mut line = line.split(|ch| ch == '|');
-        let (winning, ours) = (line.next().unwrap(), line.next().unwrap());
-        let winning: HashSet<usize> = re.find_iter(winning)
-            .map(|x| x.as_str().parse().unwrap())
-            .collect();
-        let ours: HashSet<usize> = re.find_iter(ours)
-            .map(|x| x.as_str().parse().unwrap())
-            .collect();
-
-        let num_matches = winning.intersection(&ours).count();
-        matchlist.push(num_matches);
-    }
+pub fn solve_part2(input: &Input) -> AdvInt {
+    cascade(input.lines()).map(|card| card.unwrap().copies).sum()
+}
 
-    let mut cardcounts: Vec<_> = repeat(1).take(matchlist.len()).collect();
-    for i in 0..matchlist.len() {
-        debug!("At index {} we copy {} next cards", i + 1, matchlist[i]);
-        for j in (i + 1)..=(i + matchlist[i]) {
-            debug!("(copy card {}, {} times)", j + 1, cardcounts[i]);
-            cardcounts[j] += cardcounts[i];
-        }
-        debug!("cardcounts = {:?}", cardcounts);
+/// Writes how many copies of each card are won as CSV, returning the total number of cards.
+/// Like [`cascade`], the lines can come from anywhere (such as stdin).
+pub fn write_copies_csv<L, S, W>(lines: L, mut out: W) -> Result<AdvInt, String>
+where
+    L: IntoIterator<Item = S>,
+    S: AsRef<str>,
+    W: Write,
+{
+    let err = |e: std::io::Error| e.to_string();
+    writeln!(out, "card,matches,copies").map_err(err)?;
+    let mut total: AdvInt = 0;
+    for card in cascade(lines) {
+        let card = card?;
+        writeln!(out, "{},{},{}", card.id, card.matches, card.copies).map_err(err)?;
+        total = total.checked_add(card.copies).ok_or("card count overflows")?;
     }
+    Ok(total)
+}
 
-    cardcounts.iter().sum()
+/// The CSV from [`write_copies_csv`], as a string.
+pub fn copies_csv(input: &Input) -> Result<String, String> {
+    let mut csv = vec![];
+    write_copies_csv(input.lines(), &mut csv)?;
+    String::from_utf8(csv).map_err(|e| e.to_string())
 }
 
 type AdvInt = usize;
 
+/// One scratchcard, with only what the cascade needs to know about it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Card {
+    pub id: usize,
+    pub matches: usize,
+    /// how many of this card were won (including the original)
+    pub copies: AdvInt,
+}
+
+impl Card {
+    /// Parses a line like `Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53`, counting how many
+    /// of our numbers are winning numbers (where a number repeated on either side counts once).
+    fn parse(line: &str) -> Result<Self, String> {
+        let (id, numbers) = line.split_once(':').ok_or(format!("expected `Card N:`: {line}"))?;
+        let id = id.strip_prefix("Card")
+            .and_then(|id| id.trim().parse().ok())
+            .ok_or(format!("bad card id: {id}"))?;
+        let (winning, ours) = numbers.split_once('|').ok_or(format!("expected `|`: {line}"))?;
+        let numbers = |nums: &str| parse::uints::<u32>(nums)
+            .map(|n| n.map_err(|e| format!("bad number on card {id}: {e}")))
+            .collect::<Result<HashSet<_>, _>>();
+
+        let matches = numbers(winning)?.intersection(&numbers(ours)?).count();
+        Ok(Self { id, matches, copies: 1 })
+    }
+}
+
+/// Lazily works out how many copies of each card are won, one line at a time.
+///
+/// Only the copies still owed to upcoming cards are kept (in a ring buffer which grows to the
+/// most matches on any card), so this works on piles of any length, e.g. straight from stdin.
+/// Copies owed past the last card are dropped.
+pub fn cascade<L, S>(lines: L) -> impl Iterator<Item = Result<Card, String>>
+where
+    L: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut pending = VecDeque::<AdvInt>::new();
+    lines.into_iter().map(move |line| {
+        let mut card = Card::parse(line.as_ref())?;
+        card.copies = pending.pop_front().unwrap_or(0).checked_add(1).ok_or("card count overflows")?;
+        debug!("card {} has {} copies, each winning the next {}", card.id, card.copies, card.matches);
+
+        if pending.len() < card.matches {
+            pending.resize(card.matches, 0);
+        }
+        for owed in pending.iter_mut().take(card.matches) {
+            *owed = owed.checked_add(card.copies).ok_or("card count overflows")?;
+        }
+        Ok(card)
+    })
+}
+
+#[test]
+fn long_pile() {
+    log_init();
+    // each card wins one copy of the next, so card n ends up with n copies
+    let n = 200_000;
+    let pile = (1..=n).map(|i| format!("Card {i}: 1 2 | 1 3"));
+    assert_eq!(cascade(pile).map(|card| card.unwrap().copies).sum::<AdvInt>(), n * (n + 1) / 2);
+
+    // repeated numbers only match once
+    assert_eq!(Card::parse("Card 1: 1 2 2 | 2 2 3 1").unwrap().matches, 2);
+
+    let bad = ["Card 1: 1 2 | 1 3", "Card 2: 1 2 1 3"];
+    assert!(cascade(bad).collect::<Result<Vec<_>, _>>().is_err());
+}
+
 testcase!(ex1, solve_part1, "example", 13);
 testcase!(part1, solve_part1, "input", 20667);
 testcase!(ex2, solve_part2, "example", 30);
 testcase!(part2, solve_part2, "input", 5833065);
+testcase!(csv_ex, copies_csv, "example", Ok("\
+card,matches,copies
+1,4,1
+2,2,2
+3,2,4
+4,1,8
+5,0,14
+6,0,1
+".into()));
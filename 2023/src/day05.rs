@@ -21,7 +21,7 @@
 
 #![cfg(not(doctest))]
 
-use regex::Regex;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use super::*;
 
@@ -142,26 +142,34 @@ use super::*;
 /// So, the lowest location number in this example is 35.
 ///
 /// What is the lowest location number that corresponds to any of the initial seed numbers?
-pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let mut lines = input.into_iter();
+pub fn solve_part1(input: &Input) -> AdvInt {
+    let (almanac, seed) = best_seed_part1(input);
+    almanac.convert("seed", "location", seed).unwrap()
+}
+
+/// Explains the answer to [`solve_part1`], with the full chain of conversions for the seed that
+/// ends up at the lowest location.
+pub fn explain_part1(input: &Input) -> String {
+    let (almanac, seed) = best_seed_part1(input);
+    almanac.trace("seed", "location", seed).unwrap().to_string()
+}
+
+/// Finds the initial seed with the lowest location.
+fn best_seed_part1(input: &Input) -> (Almanac, isize) {
+    let mut lines = input.lines();
 
     // list of seeds
-    let seeds = lines.next().unwrap()
-        .split_once(':').unwrap().1
-        .split_whitespace()
-        .map(|word| word.parse().unwrap())
-        .collect::<Vec<isize>>();
+    let seeds = parse_seeds(lines.next().unwrap());
     debug!("Seeds: {:?}", seeds);
 
     lines.next(); // skip empty line
 
     let almanac = Almanac::from_lines(&mut lines);
 
-    let min_loc = seeds.iter()
-        .map(|&s| almanac.translations.get_loc(s))
-        .reduce(|acc, loc| acc.min(loc));
+    let best = seeds.into_iter()
+        .min_by_key(|&s| almanac.convert("seed", "location", s).unwrap());
 
-    min_loc.unwrap()
+    (almanac, best.unwrap())
 }
 
 
@@ -191,15 +199,24 @@ pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
 ///
 /// Consider all of the initial seed numbers listed in the ranges on the first line of the almanac.
 /// What is the lowest location number that corresponds to any of the initial seed numbers?
-pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let mut lines = input.into_iter();
+pub fn solve_part2(input: &Input) -> AdvInt {
+    let (almanac, seed) = best_seed_part2(input);
+    almanac.convert("seed", "location", seed).unwrap()
+}
+
+/// Explains the answer to [`solve_part2`], with the full chain of conversions for the seed that
+/// ends up at the lowest location.
+pub fn explain_part2(input: &Input) -> String {
+    let (almanac, seed) = best_seed_part2(input);
+    almanac.trace("seed", "location", seed).unwrap().to_string()
+}
+
+/// Finds the seed within the initial seed ranges with the lowest location.
+fn best_seed_part2(input: &Input) -> (Almanac, isize) {
+    let mut lines = input.lines();
 
     // list of seeds
-    let seeds = lines.next().unwrap()
-        .split_once(':').unwrap().1
-        .split_whitespace()
-        .map(|word| word.parse().unwrap())
-        .collect::<Vec<isize>>();
+    let seeds = parse_seeds(lines.next().unwrap());
     let seeds = seeds.as_slice().chunks(2)
         .collect::<Vec<_>>();
     debug!("Seeds: {:?}", seeds);
@@ -207,21 +224,27 @@ pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
     lines.next(); // skip empty line
 
     let almanac = Almanac::from_lines(&mut lines);
+    let discontinuities = almanac.discontinuities("seed", "location").unwrap();
 
-    let min_loc = seeds.iter()
+    let best = seeds.iter()
         .flat_map(|&range| {
             debug_assert_eq!(range.len(), 2);
-            almanac.discontinuities.iter()
+            discontinuities.iter()
                 .filter(|&d| (range[0]..(range[0] + range[1])).contains(d))
         })
         .copied()
         .chain(seeds.clone()
             .into_iter()
             .map(|range| range[0])
-        ).map(|s| almanac.translations.get_loc(s))
-        .reduce(|acc, loc| acc.min(loc));
+        ).min_by_key(|&s| almanac.convert("seed", "location", s).unwrap());
+
+    (almanac, best.unwrap())
+}
 
-    min_loc.unwrap()
+fn parse_seeds(line: &str) -> Vec<isize> {
+    parse::uints(line)
+        .collect::<Result<_, _>>()
+        .unwrap()
 }
 
 type AdvInt = isize;
@@ -233,77 +256,182 @@ struct Offset {
     amount: isize,
 }
 
-struct Translations {
-    inner: [Vec<Offset>; 7],
+/// A single `X-to-Y map:` section of the almanac, converting numbers in the `from` category into
+/// numbers in the `to` category.
+#[derive(Debug)]
+struct CategoryMap {
+    from: String,
+    to: String,
+    offsets: Vec<Offset>,
 }
 
-impl Translations {
-    pub fn get_loc(&self, seed: isize) -> isize {
-        let mut result = seed;
-        for trans in &self.inner {
-            for offset in trans {
-                if result >= offset.start && result < offset.end {
-                    result += offset.amount;
-                    break;
-                }
+impl CategoryMap {
+    fn apply(&self, value: isize) -> isize {
+        self.offsets.iter()
+            .find(|offset| value >= offset.start && value < offset.end)
+            .map_or(value, |offset| value + offset.amount)
+    }
+
+    /// Every `from` value which this map sends to `value`.
+    fn invert(&self, value: isize) -> Vec<isize> {
+        let mut sources = self.offsets.iter()
+            .map(|offset| value - offset.amount)
+            .filter(|&source| self.apply(source) == value)
+            .collect::<Vec<_>>();
+        // unmapped values pass straight through
+        if self.apply(value) == value {
+            sources.push(value);
+        }
+        sources
+    }
+}
+
+/// The number a value takes on in each category along a chain of conversions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace(pub Vec<(String, isize)>);
+
+impl std::fmt::Display for Trace {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, (category, value)) in self.0.iter().enumerate() {
+            if i == 0 {
+                let mut chars = category.chars();
+                let first = chars.next().map(|ch| ch.to_ascii_uppercase());
+                write!(f, "{}{} {value}", String::from_iter(first), chars.as_str())?;
+            } else {
+                write!(f, ", {category} {value}")?;
             }
         }
-        debug!("Overall: {seed} --> {result}");
-        result
+        write!(f, ".")
     }
 }
 
-struct Almanac {
-    translations: Translations,
-    discontinuities: Vec<isize>
+/// Every map in the almanac, treated as a directed graph between named categories.
+pub struct Almanac {
+    maps: Vec<CategoryMap>,
+    /// indices into `maps` of every map leaving a given category
+    edges: HashMap<String, Vec<usize>>,
 }
 
 impl Almanac {
-    pub fn from_lines<L: Iterator<Item = String>>(lines: &mut L) -> Self {
-        // number-matcher
-        let re = Regex::new("([0-9]+) ([0-9]+) ([0-9]+)").unwrap();
-
-        // all translation tables
-        let mut translations = Translations {
-            inner: [
-                Vec::<Offset>::new(), // seed-to-soil
-                Vec::<Offset>::new(), // soil-to-fertilizer
-                Vec::<Offset>::new(), // fertilizer-to-water
-                Vec::<Offset>::new(), // water-to-light
-                Vec::<Offset>::new(), // light-to-temperature
-                Vec::<Offset>::new(), // temperature-to-humidity
-                Vec::<Offset>::new(), // humidity-to-location
-            ]
-        };
-
-        for trans in &mut translations.inner {
-            lines.next(); // skip header line
-            for line in lines.by_ref() {
-                if line.is_empty() {
-                    break;
+    /// Parses every `X-to-Y map:` section remaining in `lines`, in whatever order they appear.
+    pub fn from_lines<'a, L: Iterator<Item = &'a str>>(lines: &mut L) -> Self {
+        let header = regex!("^([a-z]+)-to-([a-z]+) map:$");
+
+        let mut maps = Vec::<CategoryMap>::new();
+        for line in lines.by_ref() {
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(caps) = header.captures(line) {
+                let (_, [from, to]) = caps.extract();
+                maps.push(CategoryMap {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    offsets: vec![],
+                });
+                continue;
+            }
+
+            let numbers = parse::uints::<isize>(line)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            let [to, from, len] = numbers[..] else {
+                panic!("expected three numbers in map entry: {line}");
+            };
+
+            let offset = Offset {
+                start: from,
+                end: from + len,
+                amount: to - from,
+            };
+            maps.last_mut()
+                .expect("map entry before any map header")
+                .offsets.push(offset);
+        }
+
+        let mut edges = HashMap::<String, Vec<usize>>::new();
+        for (i, map) in maps.iter().enumerate() {
+            debug!("Map {i}: {} -> {} ({} ranges)", map.from, map.to, map.offsets.len());
+            edges.entry(map.from.clone()).or_default().push(i);
+        }
+
+        Self { maps, edges }
+    }
+
+    /// Converts `value` from category `from` into category `to`, following the shortest chain of
+    /// maps between them. Fails if no such chain exists.
+    pub fn convert(&self, from: &str, to: &str, value: isize) -> Result<isize, String> {
+        let result = self.path(from, to)?
+            .iter()
+            .fold(value, |acc, &i| self.maps[i].apply(acc));
+        debug!("Overall: {from} {value} --> {to} {result}");
+        Ok(result)
+    }
+
+    /// Converts `value` from category `from` into category `to` like [`Almanac::convert`], but
+    /// records the number reached in every category along the way.
+    pub fn trace(&self, from: &str, to: &str, value: isize) -> Result<Trace, String> {
+        let mut trace = vec![(from.to_string(), value)];
+        for i in self.path(from, to)? {
+            let (_, prev) = trace[trace.len() - 1];
+            trace.push((self.maps[i].to.clone(), self.maps[i].apply(prev)));
+        }
+        Ok(Trace(trace))
+    }
+
+    /// Runs the maps in reverse, finding every value in category `from` which converts into
+    /// `value` in category `to`. The result is sorted, and may be empty.
+    pub fn preimages(&self, from: &str, to: &str, value: isize) -> Result<Vec<isize>, String> {
+        let mut values = vec![value];
+        for &i in self.path(from, to)?.iter().rev() {
+            values = values.into_iter()
+                .flat_map(|v| self.maps[i].invert(v))
+                .collect();
+            values.sort();
+            values.dedup();
+        }
+        Ok(values)
+    }
+
+    /// Finds the shortest chain of maps (as indices into `maps`) leading from `from` to `to` with a
+    /// breadth-first search over the categories.
+    fn path(&self, from: &str, to: &str) -> Result<Vec<usize>, String> {
+        // the map index used to first reach each category
+        let mut reached_by = HashMap::<&str, usize>::new();
+        let mut seen = HashSet::from([from]);
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                let mut path = vec![];
+                let mut cur = category;
+                while let Some(&i) = reached_by.get(cur) {
+                    path.push(i);
+                    cur = self.maps[i].from.as_str();
                 }
+                path.reverse();
+                return Ok(path);
+            }
 
-                let (_, [to, from, len]) = re.captures(line.as_str())
-                    .map(|x| x.extract()).unwrap();
-                let (to, from, len) = (
-                    to.parse::<isize>().unwrap(),
-                    from.parse::<isize>().unwrap(),
-                    len.parse::<isize>().unwrap()
-                );
-
-                let offset = Offset {
-                    start: from,
-                    end: from + len,
-                    amount: to - from,
-                };
-                trans.push(offset);
+            for &i in self.edges.get(category).into_iter().flatten() {
+                let next = self.maps[i].to.as_str();
+                if seen.insert(next) {
+                    reached_by.insert(next, i);
+                    queue.push_back(next);
+                }
             }
         }
 
-        // compute discontinuities by applying translations inversely
+        Err(format!("no conversion from {from} to {to}"))
+    }
+
+    /// Every `from` value at which the piecewise conversion into `to` may change, found by applying
+    /// each map's range boundaries inversely back to the start of the chain.
+    fn discontinuities(&self, from: &str, to: &str) -> Result<Vec<isize>, String> {
         let mut discontinuities = Vec::<isize>::new();
-        //let rtrans = translations.inner.iter().rev();
-        for trans in translations.inner.iter().rev() {
+        for &i in self.path(from, to)?.iter().rev() {
+            let trans = &self.maps[i].offsets;
             // first, reverse-translate all existing steps
             for disc in &mut discontinuities {
                 // search for any applicable translations, apply at most one
@@ -321,11 +449,64 @@ impl Almanac {
             }
         }
 
-        Self { translations, discontinuities }
+        Ok(discontinuities)
+    }
+}
+
+#[test]
+fn shuffled_maps() {
+    log_init();
+    let almanac = Almanac::from_lines(&mut get_input(get_day!(), "example").lines().skip(2));
+    let shuffled = Almanac::from_lines(&mut get_input(get_day!(), "shuffled").lines().skip(2));
+    for seed in [79, 14, 55, 13] {
+        assert_eq!(
+            almanac.convert("seed", "location", seed),
+            shuffled.convert("seed", "location", seed)
+        );
+    }
+}
+
+#[test]
+fn convert_between() {
+    log_init();
+    let almanac = Almanac::from_lines(&mut get_input(get_day!(), "example").lines().skip(2));
+    // seed 79: soil 81, fertilizer 81, water 81, light 74, temperature 78, humidity 78
+    assert_eq!(almanac.convert("soil", "humidity", 81), Ok(78));
+    assert_eq!(almanac.convert("water", "water", 81), Ok(81));
+    assert!(almanac.convert("location", "seed", 82).is_err());
+    assert!(almanac.convert("seed", "compost", 79).is_err());
+}
+
+#[test]
+fn trace_and_invert() {
+    log_init();
+    let almanac = Almanac::from_lines(&mut get_input(get_day!(), "example").lines().skip(2));
+    assert_eq!(
+        almanac.trace("seed", "location", 14).unwrap().to_string(),
+        "Seed 14, soil 14, fertilizer 53, water 49, light 42, temperature 42, humidity 43, location 43."
+    );
+    assert_eq!(almanac.trace("light", "temperature", 74).unwrap().0.last(), Some(&("temperature".into(), 78)));
+    for seed in [79, 14, 55, 13, 82] {
+        let loc = almanac.convert("seed", "location", seed).unwrap();
+        assert!(almanac.preimages("seed", "location", loc).unwrap().contains(&seed));
     }
+    assert_eq!(almanac.preimages("seed", "soil", 50), Ok(vec![98]));
+    assert_eq!(almanac.preimages("seed", "soil", 52), Ok(vec![50]));
+    assert_eq!(almanac.preimages("seed", "soil", 10), Ok(vec![10]));
+    assert!(almanac.preimages("location", "seed", 0).is_err());
 }
 
 testcase!(ex1, solve_part1, "example", 35);
 testcase!(part1, solve_part1, "input", 382895070);
 testcase!(ex2, solve_part2, "example", 46);
 testcase!(part2, solve_part2, "input", 17729182);
+testcase!(explain_ex1, explain_part1, "example",
+    "Seed 13, soil 13, fertilizer 52, water 41, light 34, temperature 34, humidity 35, location 35.");
+testcase!(explain1, explain_part1, "input",
+    "Seed 1117825174, soil 1117825174, fertilizer 264302707, water 1084564591, light 56199836, \
+    temperature 49212630, humidity 702198735, location 382895070.");
+testcase!(explain_ex2, explain_part2, "example",
+    "Seed 82, soil 84, fertilizer 84, water 84, light 77, temperature 45, humidity 46, location 46.");
+testcase!(explain2, explain_part2, "input",
+    "Seed 4002147451, soil 2330129510, fertilizer 1592413121, water 1592413121, light 258577274, \
+    temperature 258577274, humidity 17729182, location 17729182.");
@@ -20,6 +20,8 @@
 
 #![cfg(not(doctest))]
 
+use std::ops::RangeInclusive;
+
 use super::*;
 
 /// # Best Times
@@ -82,24 +84,12 @@ use super::*;
 ///
 /// Determine the number of ways you could beat the record in each race. What do you get if you
 /// multiply these numbers together?
-pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let mut lines = input.into_iter();
-
-    let times = lines.next().unwrap()
-        .split_once(':').unwrap().1
-        .split_whitespace()
-        .map(|v| v.parse().unwrap())
-        .collect::<Vec<isize>>();
-
-    let records = lines.next().unwrap()
-        .split_once(':').unwrap().1
-        .split_whitespace()
-        .map(|v| v.parse().unwrap())
-        .collect::<Vec<isize>>();
-
-    times.into_iter().zip(records)
-        .map(|(time, record)| winning_waits(time, record))
-        .reduce(|acc, w| acc*w).unwrap()
+pub fn solve_part1(input: &Input) -> AdvInt {
+    parse_races(input, false).unwrap()
+        .iter()
+        .map(|race| race.ways_to_win().unwrap())
+        .try_fold(1 as AdvInt, |acc, w| acc.checked_mul(w))
+        .expect("margin of error overflows")
 }
 
 /// # Bad Kerning
@@ -128,68 +118,130 @@ pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
 /// record, a total of 71503 ways!
 ///
 /// How many ways can you beat the record in this one much longer race?
-pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let mut lines = input.into_iter();
-
-    let time_str = lines.next().unwrap()
-        .split_once(':').unwrap().1
-        .chars()
-        .filter(|ch| !ch.is_ascii_whitespace())
-        .collect::<String>();
+pub fn solve_part2(input: &Input) -> AdvInt {
+    parse_races(input, true).unwrap()[0].ways_to_win().unwrap()
+}
 
-    let time = time_str.parse().unwrap();
+/// Finds the exact range of winning wait times for each race (or `None`, if it can't be won),
+/// optionally with the kerning fixed.
+pub fn winning_intervals(input: &Input, fix_kerning: bool) -> Result<Vec<Option<RangeInclusive<AdvInt>>>, String> {
+    parse_races(input, fix_kerning)?
+        .iter()
+        .map(Race::winning_waits)
+        .collect()
+}
 
-    let record_str = lines.next().unwrap()
-        .split_once(':').unwrap().1
-        .chars()
-        .filter(|ch| !ch.is_ascii_whitespace())
-        .collect::<String>();
-    let record = record_str.parse().unwrap();
+type AdvInt = u128;
 
-    winning_waits(time, record)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Race {
+    pub time: AdvInt,
+    pub record: AdvInt,
 }
 
-type AdvInt = isize;
-/// Computes distance traveled in a race of `total_time` given a `wait` time.
-fn distance(wait: isize, total_time: isize) -> isize {
-    (wait * total_time - wait * wait).max(0)
-}
+/// Reads the `Time:` and `Distance:` lines into races, or into one race if `fix_kerning` is set
+/// (ignoring the spaces between the numbers).
+pub fn parse_races(input: &Input, fix_kerning: bool) -> Result<Vec<Race>, String> {
+    let mut lines = input.lines();
+    let mut numbers = |label: &str| -> Result<Vec<AdvInt>, String> {
+        let line = lines.next().ok_or("premature end of input file")?;
+        let values = line.strip_prefix(label)
+            .and_then(|line| line.strip_prefix(':'))
+            .ok_or(format!("expected {label}: {line}"))?;
+        let values = match fix_kerning {
+            true => vec![values.split_whitespace().collect::<String>()],
+            false => values.split_whitespace().map(String::from).collect(),
+        };
+        values.iter()
+            .map(|v| v.parse::<AdvInt>().map_err(|e| match e.kind() {
+                std::num::IntErrorKind::PosOverflow => format!("{label} {v} overflows u128"),
+                _ => format!("bad {label}: {v}"),
+            }))
+            .collect()
+    };
 
-/// Quadratic formula solution to the range of wait times that produce wins.
-///
-/// Solves for:
-///
-/// ```
-/// distance = (total_time - wait_time) * (wait_time)
-/// ```
-///
-/// such that `distance` > `record`.
-///
-fn winning_waits(time: isize, record: isize) -> isize {
-    let (total, goal) = (time as f64, record as f64);
-    let component = ((total*total) - (4.0 * goal)).sqrt() / 2.0;
+    let times = numbers("Time")?;
+    let records = numbers("Distance")?;
+    if times.len() != records.len() {
+        return Err(format!("{} times but {} distances", times.len(), records.len()));
+    }
 
-    let mut lower = (total/2.0 - component).ceil() as isize;
-    debug!("lower: {} --> {}", lower, distance(lower, time));
+    Ok(times.into_iter()
+        .zip(records)
+        .map(|(time, record)| Race { time, record })
+        .collect())
+}
 
-    let mut upper = (total/2.0 + component).floor() as isize;
-    debug!("upper: {} --> {}", upper, distance(upper, time));
+impl Race {
+    /// How far the boat travels if the button is held for `wait` milliseconds, if that fits.
+    pub fn distance(&self, wait: AdvInt) -> Option<AdvInt> {
+        wait.checked_mul(self.time.checked_sub(wait)?)
+    }
+
+    /// The range of wait times which beat the record, if any.
+    ///
+    /// Waiting `half - d` milliseconds (where `half` is half the race, rounded down) travels
+    /// `best - d^2` for an even race or `best - d^2 - d` for an odd one, where `best` is the
+    /// furthest possible distance. So the furthest we can stray from `half` is the integer square
+    /// root of how far `best` beats the record (minus one, since we have to beat it).
+    pub fn winning_waits(&self) -> Result<Option<RangeInclusive<AdvInt>>, String> {
+        let half = self.time / 2;
+        let best = self.distance(half)
+            .ok_or(format!("a race of {} milliseconds goes too far for u128", self.time))?;
+        if best <= self.record {
+            return Ok(None);
+        }
+
+        let slack = best - self.record - 1;
+        let mut stray = slack.isqrt();
+        if self.time % 2 == 1 && stray * stray + stray > slack {
+            stray -= 1;
+        }
+
+        let lower = half - stray;
+        Ok(Some(lower..=self.time - lower))
+    }
+
+    /// The number of winning wait times.
+    pub fn ways_to_win(&self) -> Result<AdvInt, String> {
+        Ok(self.winning_waits()?.map_or(0, |waits| waits.end() - waits.start() + 1))
+    }
+}
 
-    // adjust times to the next-best if we exactly tie the record
-    if distance(lower, time) == record {
-        lower += 1;
-        debug!("lower (corrected): {} --> {}", lower, distance(lower, time));
-    };
-    if distance(upper, time) == record {
-        upper -= 1;
-        debug!("upper (corrected): {} --> {}", upper, distance(upper, time));
-    };
+#[test]
+fn winning_waits_brute() {
+    log_init();
+    for time in 0..60 {
+        for record in 0..(time * time / 4 + 2) {
+            let race = Race { time, record };
+            let wins = (0..=time).filter(|&w| race.distance(w).unwrap() > record).collect::<Vec<_>>();
+            let expected = wins.first().map(|&lower| lower..=*wins.last().unwrap());
+            assert_eq!(race.winning_waits(), Ok(expected), "{race:?}");
+        }
+    }
+}
 
-    // return the number of winning wait-times (inclusive of both bounds!)
-    upper - lower + 1
+#[test]
+fn huge_races() {
+    log_init();
+    // far past the precision of an f64
+    let time = (1 << 64) + 1;
+    let race = Race { time, record: (time / 2) * (time / 2 + 1) - 1 };
+    assert_eq!(race.winning_waits(), Ok(Some(time / 2..=time / 2 + 1)));
+    let race = Race { time: (1 << 64) - 2, record: (1 << 126) - (1 << 65) };
+    assert_eq!(race.winning_waits(), Ok(Some((1 << 63) - 1 - (1 << 32)..=(1 << 63) - 1 + (1 << 32))));
+    let race = Race { time: 1 << 66, record: 0 };
+    assert!(race.winning_waits().is_err());
+
+    let lines = |time: &str| Input::from(format!("Time: {time}\nDistance: 9 9"));
+    assert!(parse_races(&lines("99999999999999999999 99999999999999999999"), true).is_err());
+    assert!(parse_races(&lines("99999999999999999999 99999999999999999999"), false).is_ok());
+    assert!(parse_races(&lines("7"), false).is_err());
 }
 
 testcase!(ex1, solve_part1, "example", 288);
 testcase!(part1, solve_part1, "input", 449550);
 testcase!(ex2, solve_part2, "example", 71503);
 testcase!(part2, solve_part2, "input", 28360140);
+testcase!(intervals_ex1, winning_intervals, "example", Ok(vec![Some(2..=5), Some(4..=11), Some(11..=19)]), false);
+testcase!(intervals_ex2, winning_intervals, "example", Ok(vec![Some(14..=71516)]), true);
@@ -26,7 +26,7 @@
 
 #![cfg(not(doctest))]
 
-use std::collections::BinaryHeap;
+use std::collections::HashSet;
 
 use super::*;
 
@@ -99,8 +99,8 @@ use super::*;
 /// the total winnings in this example are 6440.
 ///
 /// Find the rank of every hand in your set. What are the total winnings?
-pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    get_winnings(input, RuleSet::Standard)
+pub fn solve_part1(input: &Input) -> AdvInt {
+    get_winnings(input, &Rules::standard()).unwrap()
 }
 
 /// # Jokers
@@ -138,234 +138,501 @@ pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
 /// Using the new joker rule, find the rank of every hand in your set. What are the new total
 /// winnings?
 ///
-pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    get_winnings(input, RuleSet::Joker)
+pub fn solve_part2(input: &Input) -> AdvInt {
+    get_winnings(input, &Rules::joker()).unwrap()
+}
+
+/// Finds the total winnings under the given `rules` (see [`Rules::parse`]).
+pub fn solve_with_rules(input: &Input, rules: &Input) -> Result<AdvInt, String> {
+    let rules = Rules::parse(rules.lines())?;
+    get_winnings(input, &rules)
 }
 
 type AdvInt = isize;
 
-fn get_winnings<L: IntoIterator<Item = String>>(input: L, rules: RuleSet) -> AdvInt {
-    let hands = input.into_iter()
-        .map(|l| Hand::new(l, rules))
-        .collect::<BinaryHeap<_>>()
-        .into_sorted_vec();
+/// The rules from part 1.
+const STANDARD: &str = include_str!("../inputs/day07/standard.rules");
+
+/// The rules from part 2.
+const JOKER: &str = include_str!("../inputs/day07/joker.rules");
+
+fn get_winnings(input: &Input, rules: &Rules) -> Result<AdvInt, String> {
+    let mut hands = input.lines()
+        .enumerate()
+        .map(|(i, l)| Hand::new(l, rules).map_err(|e| format!("hand {}: {e}", i + 1)))
+        .collect::<Result<Vec<_>, _>>()?;
+    hands.sort();
 
     let winnings = hands.iter().enumerate()
         .map(|(rank, hand)| {
             let rank = rank as isize + 1;
-            let cards = String::from_utf8(hand.cards.0.to_vec()).unwrap();
-            debug!("{rank:4} : {} bets {:4} ({:?})", cards, hand.bet, hand.strength);
+            let category = hand.category.map_or("nothing", |c| rules.categories[c].name.as_str());
+            debug!("{rank:4} : {} bets {:4} ({category})", hand.cards, hand.bet);
             rank * hand.bet
         })
         .sum();
 
-    winnings
+    Ok(winnings)
+}
+
+/// How a game of Camel Cards is played.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rules {
+    /// every card, from weakest to strongest
+    order: Vec<u8>,
+    /// which cards (by strength) can stand in for any other
+    wild: Vec<bool>,
+    size: usize,
+    /// from weakest to strongest
+    categories: Vec<Category>,
+    tiebreak: TieBreak,
 }
 
-#[derive(Ord, PartialOrd, Eq, PartialEq, Debug)]
-enum Strength {
-    HighCard,
-    OnePair,
-    TwoPair,
-    ThreeKind,
-    FullHouse,
-    FourKind,
-    FiveKind
+/// A kind of hand, like a full house, which needs groups of matching cards at least as large as
+/// its `pattern` (e.g. `[3, 2]`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Category {
+    pub name: String,
+    pub pattern: Vec<usize>,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-enum RuleSet {
-    Standard,
-    Joker
+/// How to order hands of the same category.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TieBreak {
+    /// compare the cards in the order they were dealt, like Camel Cards
+    Dealt,
+    /// compare the largest groups of cards first (breaking ties by strength), like poker
+    Grouped,
 }
 
-#[derive(Clone, Copy, Eq, PartialEq)]
-struct Cards([u8; 5]);
+impl Rules {
+    /// Reads rules from `key: value` lines (blank lines and `#` comments are skipped):
+    ///
+    /// - `order`: every card, from weakest to strongest
+    /// - `wild`: the cards which act as wildcards (optional)
+    /// - `size`: how many cards are in a hand
+    /// - `category`: a `name = pattern` of group sizes, once for each kind of hand from weakest
+    ///   to strongest; hands which match no category are the weakest of all
+    /// - `tiebreak`: either `dealt` or `grouped`
+    pub fn parse<'a, L: IntoIterator<Item = &'a str>>(lines: L) -> Result<Self, String> {
+        let (mut order, mut wild, mut size, mut tiebreak) = (None, None, None, None);
+        let mut categories = vec![];
+
+        for (i, line) in lines.into_iter().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let err = |msg: &str| format!("line {}: {msg}: {line}", i + 1);
+            let (key, value) = line.split_once(':').ok_or_else(|| err("expected `key: value`"))?;
+            let value = value.trim();
+            match key.trim() {
+                "order" => order = Some(value.bytes().filter(|b| !b.is_ascii_whitespace()).collect::<Vec<_>>()),
+                "wild" => wild = Some(value.bytes().filter(|b| !b.is_ascii_whitespace()).collect::<Vec<_>>()),
+                "size" => size = Some(value.parse::<usize>().map_err(|_| err("bad hand size"))?),
+                "category" => {
+                    let (name, pattern) = value.split_once('=').ok_or_else(|| err("expected `name = pattern`"))?;
+                    let mut pattern = pattern.split_whitespace()
+                        .map(|n| n.parse::<usize>().ok().filter(|&n| n > 0))
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or_else(|| err("bad pattern"))?;
+                    pattern.sort_by(|a, b| b.cmp(a));
+                    categories.push(Category { name: name.trim().to_string(), pattern });
+                },
+                "tiebreak" => tiebreak = Some(match value {
+                    "dealt" => TieBreak::Dealt,
+                    "grouped" => TieBreak::Grouped,
+                    _ => return Err(err("unknown tiebreak")),
+                }),
+                _ => return Err(err("unknown key")),
+            }
+        }
+
+        let order = order.ok_or("missing card order")?;
+        let size = size.ok_or("missing hand size")?;
+        let tiebreak = tiebreak.ok_or("missing tiebreak")?;
+        if order.is_empty() {
+            return Err("no cards in the card order".into());
+        }
+        if (1..order.len()).any(|i| order[..i].contains(&order[i])) {
+            return Err("card order repeats a card".into());
+        }
+        let mut is_wild = vec![false; order.len()];
+        for card in wild.unwrap_or_default() {
+            let strength = order.iter().position(|&c| c == card)
+                .ok_or(format!("wildcard {} isn't in the card order", card as char))?;
+            is_wild[strength] = true;
+        }
+        if size == 0 {
+            return Err("hands need at least one card".into());
+        }
+        if categories.is_empty() {
+            return Err("no hand categories".into());
+        }
+        if let Some(c) = categories.iter().find(|c| c.pattern.iter().sum::<usize>() > size) {
+            return Err(format!("category {} needs more than {size} cards", c.name));
+        }
+
+        Ok(Self { order, wild: is_wild, size, categories, tiebreak })
+    }
+
+    /// The rules from part 1.
+    pub fn standard() -> Self {
+        Self::parse(STANDARD.lines()).unwrap()
+    }
+
+    /// The rules from part 2, where J is a weak wildcard.
+    pub fn joker() -> Self {
+        Self::parse(JOKER.lines()).unwrap()
+    }
+
+    /// The strongest category (by index) the cards (given by strength) can make, if any.
+    pub fn categorize(&self, cards: &[usize]) -> Option<usize> {
+        let mut counts = vec![0; self.order.len()];
+        cards.iter().for_each(|&c| counts[c] += 1);
+        self.categorize_counts(&counts)
+    }
+
+    /// Like [`Rules::categorize`], but given how many of each card (by strength) are in the hand.
+    fn categorize_counts(&self, counts: &[usize]) -> Option<usize> {
+        let wilds = counts.iter().zip(&self.wild).filter(|(_, &w)| w).map(|(&n, _)| n).sum();
+        let groups = counts.iter()
+            .enumerate()
+            .filter(|&(c, &n)| n > 0 && !self.wild[c])
+            .map(|(_, &n)| n)
+            .collect::<Vec<_>>();
+
+        groupings(groups, wilds).iter()
+            .filter_map(|groups| self.categories.iter().rposition(|c| {
+                c.pattern.len() <= groups.len() && c.pattern.iter().zip(groups).all(|(p, g)| g >= p)
+            }))
+            .max()
+    }
+
+    /// How the cards (given by strength) compare against another hand of the same category.
+    fn tiebreak_key(&self, cards: &[usize]) -> Vec<usize> {
+        match self.tiebreak {
+            TieBreak::Dealt => cards.to_vec(),
+            TieBreak::Grouped => {
+                let mut counts = vec![0; self.order.len()];
+                cards.iter().for_each(|&c| counts[c] += 1);
+                let mut key = cards.to_vec();
+                key.sort_by_key(|&c| std::cmp::Reverse((counts[c], c)));
+                key
+            },
+        }
+    }
+
+    /// How often each category is dealt: exactly, unless there are more than
+    /// [`EXHAUSTIVE_LIMIT`] different hands (ignoring order) to check, in which case `samples`
+    /// random hands are dealt instead.
+    pub fn distribution(&self, samples: u64) -> Distribution {
+        // stars and bars, stopping early once it's clearly too many
+        let mut multisets: u128 = 1;
+        for i in 1..=self.size as u128 {
+            multisets = multisets * (self.order.len() as u128 + i - 1) / i;
+            if multisets > EXHAUSTIVE_LIMIT {
+                return self.sample_distribution(samples, 0x9e37_79b9_7f4a_7c15);
+            }
+        }
+        self.exact_distribution()
+    }
+
+    /// Counts the category of every possible hand, where each card is dealt from an endless deck
+    /// (so all `order.len() ^ size` hands are equally likely).
+    pub fn exact_distribution(&self) -> Distribution {
+        let mut dist = Distribution::new(self, true);
+        let mut counts = vec![0; self.order.len()];
+        self.count_multisets(&mut counts, 0, self.size, 1, &mut dist);
+        dist
+    }
+
+    /// Picks out the next card's count for every multiset of hands, where `ways` is how many
+    /// orders the cards picked so far could have been dealt in.
+    fn count_multisets(&self, counts: &mut [usize], card: usize, left: usize, ways: u128, dist: &mut Distribution) {
+        if card == counts.len() - 1 {
+            counts[card] = left;
+            dist.add(self.categorize_counts(counts), ways);
+            return;
+        }
+        // choose(left, n), built up as n grows
+        let mut choose = 1;
+        for n in 0..=left {
+            if n > 0 {
+                choose = choose * (left - n + 1) as u128 / n as u128;
+            }
+            counts[card] = n;
+            self.count_multisets(counts, card + 1, left - n, ways * choose, dist);
+        }
+        counts[card] = 0;
+    }
 
-impl TryFrom<&[u8]> for Cards {
-    type Error = ();
-    fn try_from(other: &[u8]) -> Result<Self, Self::Error> {
-        match other.try_into() {
-            Ok(cards) => Ok(Cards(cards)),
-            Err(_) => Err(()),
+    /// Deals `samples` random hands (with a fixed `seed`, to keep runs repeatable).
+    pub fn sample_distribution(&self, samples: u64, seed: u64) -> Distribution {
+        let mut dist = Distribution::new(self, false);
+        let mut rng = XorShift(seed.max(1));
+        let mut counts = vec![0; self.order.len()];
+        for _ in 0..samples {
+            counts.fill(0);
+            for _ in 0..self.size {
+                counts[rng.below(self.order.len() as u64) as usize] += 1;
+            }
+            dist.add(self.categorize_counts(&counts), 1);
         }
+        dist
     }
 }
 
-impl TryFrom<&str> for Cards {
-    type Error = ();
-    fn try_from(other: &str) -> Result<Self, Self::Error> {
-        Cards::try_from(other.as_bytes())
+/// Every way (as group sizes, largest first) that some wildcards can join existing groups of
+/// matching cards, or start new ones.
+fn groupings(mut groups: Vec<usize>, wilds: usize) -> Vec<Vec<usize>> {
+    // keep each grouping sorted, so the set merges the ones which only differ in order
+    groups.sort_by(|a, b| b.cmp(a));
+    let mut current = HashSet::from([groups]);
+    for _ in 0..wilds {
+        current = current.iter()
+            .flat_map(|groups| (0..=groups.len()).map(|i| {
+                let mut next = groups.clone();
+                match next.get_mut(i) {
+                    Some(n) => *n += 1,
+                    None => next.push(1),
+                }
+                next.sort_by(|a, b| b.cmp(a));
+                next
+            }))
+            .collect();
     }
+    current.into_iter().collect()
 }
 
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd)]
 struct Hand {
-    cards: Cards,
+    // field order matters: hands are compared by category, then by tiebreak
+    category: Option<usize>,
+    key: Vec<usize>,
+    cards: String,
     bet: isize,
-    strength: Strength,
-    rules: RuleSet,
 }
 
 impl Hand {
-    pub fn new<L: AsRef<str>>(line: L, rules: RuleSet) -> Self {
-        let line = line.as_ref()
-            .split_whitespace()
-            .collect::<Vec<_>>();
-        let cards: Cards = line[0].try_into().unwrap();
-        let bet = line[1].parse().unwrap();
-        let strength = Hand::strength(&cards, rules);
-        Hand { cards, bet, strength, rules }
+    pub fn new(line: &str, rules: &Rules) -> Result<Self, String> {
+        let (cards, bet) = line.split_once(' ').ok_or(format!("expected cards and a bet: {line}"))?;
+        let bet = bet.trim().parse().map_err(|_| format!("bad bet: {bet}"))?;
+        let strengths = cards.bytes()
+            .map(|card| rules.order.iter().position(|&c| c == card)
+                .ok_or(format!("unknown card: {}", card as char)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if strengths.len() != rules.size {
+            return Err(format!("expected {} cards: {cards}", rules.size));
+        }
+
+        Ok(Hand {
+            category: rules.categorize(&strengths),
+            key: rules.tiebreak_key(&strengths),
+            cards: cards.to_string(),
+            bet,
+        })
     }
+}
 
-    pub fn strength(cards: &Cards, rules: RuleSet) -> Strength {
-        debug!("HAND: {}", cards.0.iter().map(|&c| c as char).collect::<String>());
-        let mut unique = cards.0.to_vec();
-        unique.sort_by_key(|a| card_strength(a, rules));
-        unique.dedup();
-        debug!("unique: {}", unique.iter().map(|&c| c as char).collect::<String>());
-
-        let mut counts = unique.iter()
-            .filter_map(|&c| match cards.0.iter().filter(|&&ch| ch == c).count() {
-                0 => None,
-                x => Some((c, x)),
-            })
-            .collect::<Vec<_>>();
-        debug!("counts: {:?}", counts.iter().map(|(ch, cnt)| format!("{}: {}", *ch as char, cnt)).collect::<Vec<_>>());
-
-        if rules == RuleSet::Joker {
-            let jokers = counts.iter()
-                .find(|&&(c, _)| c == b'J')
-                .map_or(0, |&(_, cnt)| cnt);
-            counts.retain(|&(c, _)| c != b'J');
-            debug!("*count: {:?} ({jokers} jokers)", counts.iter().map(|(ch, cnt)| format!("{}: {}", *ch as char, cnt)).collect::<Vec<_>>());
-
-            // re-sort to obtain largest counts
-            let mut counts = counts.iter()
-                .map(|&(_, cnt)| cnt)
-                .collect::<Vec<_>>();
-            counts.sort();
-            let most = counts.pop();
-            let nextmost = counts.pop();
-            hand_strength_joker(jokers, most, nextmost)
-        } else {
-            let mut counts = counts.iter()
-                .map(|&(_, cnt)| cnt)
-                .collect::<Vec<_>>();
-            counts.sort();
-            let most = counts.pop();
-            let nextmost = counts.pop();
-            hand_strength_standard(most, nextmost)
+/// How many hands (ignoring order) [`Rules::distribution`] will check before it starts sampling.
+pub const EXHAUSTIVE_LIMIT: u128 = 2_000_000;
+
+/// How many hands fall into each category of some rules.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Distribution {
+    /// whether every hand was counted, rather than a random sample
+    pub exact: bool,
+    pub total: u128,
+    /// the names of each category, weakest first
+    pub names: Vec<String>,
+    pub counts: Vec<u128>,
+    /// hands which fit no category
+    pub unmatched: u128,
+}
+
+impl Distribution {
+    fn new(rules: &Rules, exact: bool) -> Self {
+        Self {
+            exact,
+            total: 0,
+            names: rules.categories.iter().map(|c| c.name.clone()).collect(),
+            counts: vec![0; rules.categories.len()],
+            unmatched: 0,
         }
     }
-}
 
-impl std::fmt::Debug for Hand {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Hand")
-            .field("cards", &self.cards)
-            .field("strength", &self.strength)
-            .field("bet", &self.bet)
-            .field("rules", &self.rules)
-            .finish()
+    fn add(&mut self, category: Option<usize>, hands: u128) {
+        match category {
+            Some(c) => self.counts[c] += hands,
+            None => self.unmatched += hands,
+        }
+        self.total += hands;
     }
-}
 
-impl std::fmt::Debug for Cards {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let cards = String::from_utf8(self.0.to_vec()).unwrap();
-        write!(f, "{}", cards.as_str())
+    /// The share of hands (from 0 to 1) in the named category, if the rules have one.
+    pub fn share(&self, name: &str) -> Option<f64> {
+        let i = self.names.iter().position(|n| n == name)?;
+        Some(self.counts[i] as f64 / self.total as f64)
     }
 }
 
-impl std::cmp::PartialEq for Hand {
-    fn eq(&self, other: &Self) -> bool {
-        self.cards == other.cards
+impl std::fmt::Display for Distribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let kind = if self.exact { "hands" } else { "sampled hands" };
+        writeln!(f, "{} {kind}", self.total)?;
+        let width = self.names.iter().map(|n| n.len()).max().unwrap_or(0).max(9);
+        let rows = self.names.iter().map(String::as_str).zip(&self.counts).rev()
+            .chain((self.unmatched > 0).then_some(("unmatched", &self.unmatched)));
+        for (name, &count) in rows {
+            let percent = 100.0 * count as f64 / self.total as f64;
+            writeln!(f, "{name:>width$}: {count:>10} ({percent:8.4}%)")?;
+        }
+        Ok(())
     }
 }
 
-impl std::cmp::Eq for Hand {}
-impl std::cmp::PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+/// A small, seeded pseudo-random number generator (xorshift64*).
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A number in `0..n`, with a negligible bias for the small `n` dealt with here.
+    fn below(&mut self, n: u64) -> u64 {
+        ((self.next() as u128 * n as u128) >> 64) as u64
     }
 }
 
-impl std::cmp::Ord for Hand {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        match self.strength.cmp(&other.strength) {
-            std::cmp::Ordering::Equal => {
-                let left = self.cards.0.iter().map(|c| card_strength(c, self.rules));
-                let right = other.cards.0.iter().map(|c| card_strength(c, self.rules));
-                left.cmp(right)
-            }
-            order => order
+/// Describes how often each category comes up under each set of (named) `rules`.
+pub fn describe_distributions(rules: &[(&str, Rules)], samples: u64) -> String {
+    rules.iter()
+        .map(|(name, rules)| format!("{name}: {}", rules.distribution(samples)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Ranks the hands under each set of (named) `rules`, as one line per hand (in the order they're
+/// listed) with a column of ranks for each set of rules.
+pub fn rank_table(input: &Input, rules: &[(&str, Rules)]) -> Result<String, String> {
+    let lines = input.lines().collect::<Vec<_>>();
+    let mut ranks = vec![vec![0; rules.len()]; lines.len()];
+    for (r, (_, rules)) in rules.iter().enumerate() {
+        let mut hands = lines.iter()
+            .enumerate()
+            .map(|(i, line)| Ok((Hand::new(line, rules).map_err(|e| format!("hand {}: {e}", i + 1))?, i)))
+            .collect::<Result<Vec<_>, String>>()?;
+        hands.sort();
+        for (rank, (_, i)) in hands.iter().enumerate() {
+            ranks[*i][r] = rank + 1;
         }
     }
-}
 
-const fn card_strength(card: &u8, rules: RuleSet) -> isize {
-    match card {
-        b'A' => 14,
-        b'K' => 13,
-        b'Q' => 12,
-        b'J' => match rules {
-            RuleSet::Standard => 11,
-            RuleSet::Joker => 1,
-        },
-        b'T' => 10,
-        b'9' => 9,
-        b'8' => 8,
-        b'7' => 7,
-        b'6' => 6,
-        b'5' => 5,
-        b'4' => 4,
-        b'3' => 3,
-        b'2' => 2,
-        _ => 0,
+    let width = lines.iter().map(|l| l.split(' ').next().unwrap().len()).max().unwrap_or(0).max(4);
+    let mut table = format!("{:width$}", "hand");
+    for (name, _) in rules {
+        table += &format!(" {name:>8}");
     }
+    for (line, ranks) in lines.iter().zip(&ranks) {
+        table += &format!("\n{:width$}", line.split(' ').next().unwrap());
+        for (rank, (name, _)) in ranks.iter().zip(rules) {
+            table += &format!(" {rank:>w$}", w = name.len().max(8));
+        }
+    }
+    Ok(table)
 }
 
-const fn hand_strength_standard(first: Option<usize>, second: Option<usize>) -> Strength {
-    match (first, second) {
-        (Some(5), _)       => Strength::FiveKind,
-        (Some(4), _)       => Strength::FourKind,
-        (Some(3), Some(2)) => Strength::FullHouse,
-        (Some(3), _)       => Strength::ThreeKind,
-        (Some(2), Some(2)) => Strength::TwoPair,
-        (Some(2), _)       => Strength::OnePair,
-        _                  => Strength::HighCard,
-    }
+/// The rules in each of `files`, named after the file.
+#[cfg(test)]
+fn rules_files<'a>(files: &[&'a str]) -> Vec<(&'a str, Rules)> {
+    files.iter()
+        .map(|&file| (file, Rules::parse(get_input(get_day!(), file).lines()).unwrap()))
+        .collect()
 }
 
-const fn hand_strength_joker(jokers: usize, first: Option<usize>, second: Option<usize>) -> Strength {
-    match (jokers, first, second) {
-        (_, Some(5), _)       => Strength::FiveKind,
-        (1, Some(4), _)       => Strength::FiveKind,
-        (2, Some(3), _)       => Strength::FiveKind,
-        (3, Some(2), _)       => Strength::FiveKind,
-        (4, Some(1), _)       => Strength::FiveKind,
-        (5, _, _)             => Strength::FiveKind,
-        (0, Some(4), _)       => Strength::FourKind,
-        (1, Some(3), _)       => Strength::FourKind,
-        (2, Some(2), _)       => Strength::FourKind,
-        (3, Some(1), _)       => Strength::FourKind,
-        (4, _, _)             => Strength::FourKind,
-        (0, Some(3), Some(2)) => Strength::FullHouse,
-        (1, Some(2), Some(2)) => Strength::FullHouse,
-        (3, _, _)             => Strength::FullHouse,
-        (0, Some(3), _)       => Strength::ThreeKind,
-        (1, Some(2), _)       => Strength::ThreeKind,
-        (2, Some(1), _)       => Strength::ThreeKind,
-        (0, Some(2), Some(2)) => Strength::TwoPair,
-        (2, _, _)             => Strength::TwoPair,
-        (0, Some(2), _)       => Strength::OnePair,
-        (1, Some(1), _)       => Strength::OnePair,
-        _                     => Strength::HighCard,
-    }
+#[test]
+fn categories() {
+    log_init();
+    let category = |rules: &Rules, cards: &str| {
+        let hand = Hand::new(&format!("{cards} 1"), rules).unwrap();
+        hand.category.map(|c| rules.categories[c].name.clone())
+    };
+    let (standard, joker) = (Rules::standard(), Rules::joker());
+    assert_eq!(category(&standard, "KTJJT").as_deref(), Some("two pair"));
+    assert_eq!(category(&joker, "KTJJT").as_deref(), Some("four of a kind"));
+    assert_eq!(category(&joker, "JJJJJ").as_deref(), Some("five of a kind"));
+    assert_eq!(category(&joker, "2345J").as_deref(), Some("one pair"));
+    assert_eq!(category(&joker, "2233J").as_deref(), Some("full house"));
+
+    // a category which is only reached by spreading the wildcards across groups
+    let rules = Rules::parse([
+        "order: 23456789TJQKA", "wild: 2", "size: 5",
+        "category: pair = 2", "category: triple = 3", "category: two pair = 2 2",
+        "tiebreak: grouped",
+    ]).unwrap();
+    assert_eq!(category(&rules, "2AK22").as_deref(), Some("two pair"));
+    assert_eq!(category(&rules, "AAKQ2").as_deref(), Some("two pair"));
+    assert_eq!(category(&rules, "AKQJT"), None);
+
+    // groupings which only differ in order are the same grouping
+    let mut wild = groupings(vec![1, 1], 2);
+    wild.sort();
+    assert_eq!(wild, [vec![1, 1, 1, 1], vec![2, 1, 1], vec![2, 2], vec![3, 1]]);
+    assert!(Hand::new("AAKQ 1", &rules).is_err());
+    assert!(Hand::new("AAKQ1 1", &rules).is_err());
+
+    assert!(Rules::parse(["order: 23", "wild: 4", "size: 1", "category: a = 1", "tiebreak: dealt"]).is_err());
+    assert!(Rules::parse(["order: 23", "size: 1", "category: a = 2", "tiebreak: dealt"]).is_err());
+    assert!(Rules::parse(["order: 232", "size: 1", "category: a = 1", "tiebreak: dealt"]).is_err());
+    assert!(Rules::parse(["order:", "size: 1", "category: a = 1", "tiebreak: dealt"]).is_err());
 }
 
+#[test]
+fn distributions() {
+    log_init();
+    let standard = Rules::standard().distribution(0);
+    assert!(standard.exact);
+    assert_eq!(standard.total, 13u128.pow(5));
+    assert_eq!(standard.counts, [154440, 171600, 25740, 17160, 1560, 780, 13]);
+
+    // jokers only ever help
+    let joker = Rules::joker().distribution(0);
+    assert_eq!(joker.total, standard.total);
+    assert!(joker.counts[0] < standard.counts[0]);
+    assert!(joker.counts[6] > standard.counts[6]);
+    let report = describe_distributions(&rules_files(&["joker.rules"]), 0);
+    assert!(report.starts_with("joker.rules: 371293 hands\n five of a kind:        373 (  0.1005%)\n"));
+
+    let sampled = Rules::joker().sample_distribution(200_000, 1);
+    assert!(!sampled.exact);
+    for name in &joker.names {
+        assert!((sampled.share(name).unwrap() - joker.share(name).unwrap()).abs() < 0.005, "{name}");
+    }
+
+    // too many hands to count
+    let rules = Rules::parse(STANDARD.replace("size: 5", "size: 30").lines()).unwrap();
+    let dist = rules.distribution(1000);
+    assert!(!dist.exact);
+    assert_eq!(dist.total, 1000);
+    assert_eq!(dist.counts[0], 0);
+}
 
 testcase!(ex1, solve_part1, "example", 6440);
 testcase!(part1, solve_part1, "input", 249748283);
 testcase!(ex2, solve_part2, "example", 5905);
 testcase!(part2, solve_part2, "input", 248029057);
+testcase!(standard_file, solve_with_rules, "input", Ok(249748283), &get_input(get_day!(), "standard.rules"));
+testcase!(joker_file, solve_with_rules, "input", Ok(248029057), &get_input(get_day!(), "joker.rules"));
+testcase!(deuces, solve_with_rules, "example6", Ok(7510), &get_input(get_day!(), "deuces.rules"));
+testcase!(ranks_ex, rank_table, "example", Ok("\
+hand  standard.rules joker.rules poker.rules
+32T3K              1           1           1
+T55J5              4           3           4
+KK677              3           2           3
+KTJJT              2           5           2
+QQQJA              5           4           5".into()), &rules_files(&["standard.rules", "joker.rules", "poker.rules"]));
@@ -12,7 +12,7 @@
 
 #![cfg(not(doctest))]
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use super::*;
 
@@ -56,42 +56,12 @@ use super::*;
 /// ```
 ///
 /// Starting at AAA, follow the left/right instructions. How many steps are required to reach ZZZ?
-pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let mut lines = input.into_iter();
-    let instruction = lines.next()
-        .expect("Premature end of input file")
-        .chars()
-        .collect::<Vec<_>>();
-
-    // grab the next line, verify it's empty
-    debug_assert!(lines.next().expect("early EOF").is_empty());
-
-    let map = lines.filter_map(|line| {
-        let bytes: [u8; 16] = line.as_bytes().try_into().ok()?;
-        let node = bytes[0..3].try_into().unwrap();
-        let left = bytes[7..10].try_into().unwrap();
-        let right = bytes[12..15].try_into().unwrap();
-        Some((node, (left, right)))
-    }).collect::<HashMap<Node, (Node, Node)>>();
-
-
-    debug!("The Map: {map:?}");
-
-    let mut node: Node = "AAA".try_into().unwrap();
-    let end: Node = "ZZZ".try_into().unwrap();
-    let mut instructions = instruction.iter().cycle();
-    let mut steps = 0;
-
-    while node != end {
-        node = match instructions.next() {
-            Some(&'L') => map[&node].0,
-            Some(&'R') => map[&node].1,
-            _ => panic!("BAD DIRECTION"),
-        };
-        steps += 1;
-    }
-
-    steps
+pub fn solve_part1(input: &Input) -> AdvInt {
+    let network = Network::parse(input, "LR").unwrap();
+    debug!("The Map: {network:?}");
+    network.solve(|node| node == "AAA", |node| node == "ZZZ")
+        .unwrap()
+        .expect("ZZZ is never reached")
 }
 
 /// --- Part Two ---
@@ -140,163 +110,325 @@ pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
 ///
 /// Simultaneously start on every node that ends with A. How many steps does it take before you're
 /// only on nodes that end with Z?
-pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let mut lines = input.into_iter();
-    let instruction = lines.next()
-        .expect("Premature end of input file")
-        .chars()
-        .collect::<Vec<_>>();
+pub fn solve_part2(input: &Input) -> AdvInt {
+    let network = Network::parse(input, "LR").unwrap();
+    network.solve(|node| node.ends_with('A'), |node| node.ends_with('Z'))
+        .unwrap()
+        .expect("the ghosts never line up")
+}
 
-    // grab the next line, verify it's empty
-    debug_assert!(lines.next().expect("early EOF").is_empty());
+/// Solves a network whose instructions are written with the `branches` letters (one per branch
+/// of each node), with a ghost starting on every node ending in `start_suffix` and stopping once
+/// they're all on nodes ending in `end_suffix`.
+pub fn solve_general(input: &Input, branches: &str, start_suffix: &str, end_suffix: &str) -> Result<Option<AdvInt>, String> {
+    let network = Network::parse(input, branches)?;
+    network.solve(|node| node.ends_with(start_suffix), |node| node.ends_with(end_suffix))
+}
 
-    let map = lines.filter_map(|line| {
-        let bytes: [u8; 16] = line.as_bytes().try_into().ok()?;
-        let node = bytes[0..3].try_into().unwrap();
-        let left = bytes[7..10].try_into().unwrap();
-        let right = bytes[12..15].try_into().unwrap();
-        Some((node, (left, right)))
-    }).collect::<HashMap<Node, (Node, Node)>>();
+type AdvInt = u128;
 
-    debug!("The Map:");
-    for (n, (l, r)) in map.iter() {
-        debug!("  {n:?} = {l:?}, {r:?}");
-    }
+/// The instructions and nodes of a map, with nodes referred to by their index.
+#[derive(Debug)]
+pub struct Network {
+    /// which branch (by index) each instruction takes
+    instructions: Vec<usize>,
+    names: Vec<String>,
+    branches: Vec<Vec<usize>>,
+}
 
-    // figure out each starting node
-    let mut traversals = map.keys()
-        .filter_map(|&n| if n.is_start() { Some(Traversal::new(&n)) } else { None })
-        .collect::<Vec<Traversal>>();
-    // our instructions form an infinite loop
-    let mut instructions = instruction.iter().cycle();
+/// Where one ghost ends up over time: after a `tail` of steps, its (node, instruction) state
+/// repeats every `period` steps forever.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GhostCycle {
+    pub start: String,
+    pub tail: usize,
+    pub period: usize,
+    /// every step before `tail + period` where the ghost is on an end node
+    pub ends: Vec<usize>,
+}
 
-    debug!("Instructions: {}", String::from_iter(instruction.iter()));
-    debug!("Instructions Length: {}", instruction.len());
+impl GhostCycle {
+    pub fn is_end_at(&self, step: u128) -> bool {
+        let step = match step < (self.tail + self.period) as u128 {
+            true => step as usize,
+            false => self.tail + ((step - self.tail as u128) % self.period as u128) as usize,
+        };
+        self.ends.binary_search(&step).is_ok()
+    }
+}
 
-    // run our iterations until every pair has looped once
-    let mut step = 0;
-    while traversals.iter().any(|t| t.repeat.is_none()) {
+impl Network {
+    /// Parses the instructions, a blank line, and then one `NAME = (LEFT, RIGHT, ...)` line per
+    /// node. Names can be any length, and each node needs one branch per letter in `branches`
+    /// (which the instructions are written in).
+    pub fn parse(input: &Input, branches: &str) -> Result<Self, String> {
+        let letters = branches.chars().collect::<Vec<_>>();
+        let mut lines = input.lines();
+        let instructions = lines.next()
+            .ok_or("premature end of input file")?
+            .chars()
+            .map(|ch| letters.iter().position(|&l| l == ch).ok_or(format!("bad direction: {ch}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        if instructions.is_empty() {
+            return Err("no instructions".into());
+        }
 
-        // step 1 = the location after the first traverse
-        let dir = instructions.next().unwrap();
-        step += 1;
+        let mut names = vec![];
+        let mut targets = vec![];
+        for (i, line) in lines.enumerate().filter(|(_, line)| !line.is_empty()) {
+            let malformed = || format!("malformed node on line {}: {line}", i + 2);
+            let (name, rest) = line.split_once('=').ok_or_else(malformed)?;
+            let rest = rest.trim()
+                .strip_prefix('(')
+                .and_then(|rest| rest.strip_suffix(')'))
+                .ok_or_else(malformed)?;
+            let to = rest.split(',').map(|n| n.trim().to_string()).collect::<Vec<_>>();
+            if to.len() != letters.len() {
+                return Err(format!("expected {} branches on line {}: {line}", letters.len(), i + 2));
+            }
+            names.push(name.trim().to_string());
+            targets.push(to);
+        }
 
-        // for each starting node's traversal
-        for t in traversals.iter_mut() {
-            // move it along the map according to the current direction
-            t.node = match &dir {
-                'L' => map[&t.node].0,
-                'R' => map[&t.node].1,
-                _ => panic!("bad direction"),
-            };
+        let index = names.iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect::<HashMap<_, _>>();
+        if index.len() != names.len() {
+            return Err("a node is defined more than once".into());
+        }
+        let branches = targets.iter()
+            .map(|to| to.iter()
+                .map(|name| index.get(name.as_str()).copied().ok_or(format!("undefined node: {name}")))
+                .collect::<Result<Vec<_>, _>>())
+            .collect::<Result<Vec<_>, _>>()?;
 
-            // record if we visit an acceptable end-node
-            if t.node.is_end() {
-                t.ends.push(step);
+        Ok(Self { instructions, names, branches })
+    }
 
-                // if we see an end node twice, we assume it's a loop
-                if t.ends.len() == 2 {
-                    t.repeat = Some(Repeat {
-                        start: t.ends[0],
-                        end: t.ends[1],
-                    });
-                }
+    /// Follows the instructions from `start` until its (node, instruction) state repeats.
+    pub fn trace<E: Fn(&str) -> bool>(&self, start: usize, is_end: E) -> GhostCycle {
+        let len = self.instructions.len();
+        // the step each state was first seen on
+        let mut seen = vec![usize::MAX; self.names.len() * len];
+        let mut ends = vec![];
+        let mut node = start;
+        let mut step = 0;
+
+        loop {
+            let state = node * len + step % len;
+            if seen[state] != usize::MAX {
+                let tail = seen[state];
+                return GhostCycle {
+                    start: self.names[start].clone(),
+                    tail,
+                    period: step - tail,
+                    ends,
+                };
+            }
+            seen[state] = step;
+            if is_end(&self.names[node]) {
+                ends.push(step);
             }
+            node = self.branches[node][self.instructions[step % len]];
+            step += 1;
         }
     }
 
-    // verify an assumption about our input (to make calculation easier)
-    let uniform_loops = traversals.iter().all(|t| t.repeat.is_some_and(|x| x.end - x.start == x.start));
-    debug_assert!(uniform_loops);
-    let lcm = traversals.iter_mut()
-        .map(|t| t.repeat.unwrap().start)
-        .reduce(|lcm, mut cur| {
-            // fun fact, the loops are prime numbers times the instruction length
-            // (but NOT in the example code, so we have to condition it here >.>)
-            if cur % (instruction.len() as AdvInt) == 0 && cur != instruction.len() as AdvInt {
-                debug!("shortening cur {cur} to {}", cur / (instruction.len() as AdvInt));
-                cur /= instruction.len() as AdvInt
-            };
-            lcm * cur
-        });
-
-    lcm.unwrap()
-}
+    /// Traces a ghost from every start node.
+    pub fn ghosts<S, E>(&self, is_start: S, is_end: E) -> Vec<GhostCycle>
+    where
+        S: Fn(&str) -> bool,
+        E: Fn(&str) -> bool,
+    {
+        (0..self.names.len())
+            .filter(|&n| is_start(&self.names[n]))
+            .map(|n| self.trace(n, &is_end))
+            .collect()
+    }
 
-type AdvInt = u128;
+    /// The first step on which the ghosts from every start node are all on end nodes, if ever.
+    ///
+    /// Fails if the ghosts don't line up before the step count overflows, since they might still
+    /// line up after that.
+    pub fn solve<S, E>(&self, is_start: S, is_end: E) -> Result<Option<u128>, String>
+    where
+        S: Fn(&str) -> bool,
+        E: Fn(&str) -> bool,
+    {
+        let ghosts = self.ghosts(is_start, is_end);
+        for ghost in &ghosts {
+            debug!("{ghost:?}");
+        }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
-struct Repeat {
-    start: AdvInt,
-    end: AdvInt,
-}
+        // until every ghost has settled into its cycle, just check each step
+        let Some(settled) = ghosts.iter().map(|g| g.tail as u128).max() else {
+            return Ok(None);
+        };
+        if let Some(step) = (0..settled).find(|&step| ghosts.iter().all(|g| g.is_end_at(step))) {
+            return Ok(Some(step));
+        }
 
-struct Traversal {
-    node: Node,
-    ends: Vec<AdvInt>,
-    repeat: Option<Repeat>,
+        // afterwards, each ghost is on an end node whenever the step is congruent to one of its
+        // cyclic ends, so combine every choice of those with the chinese remainder theorem. The
+        // combined congruences all share one modulus, so a ghost whose period divides it (such as
+        // one with the same period as an earlier ghost) can only rule some of them out
+        let mut modulus = 1;
+        let mut residues = vec![0];
+        for (i, ghost) in ghosts.iter().enumerate() {
+            let period = ghost.period as u128;
+            let ends = ghost.ends.iter()
+                .filter(|&&end| end >= ghost.tail)
+                .map(|&end| end as u128 % period)
+                .collect::<HashSet<_>>();
+            if modulus % period == 0 {
+                residues.retain(|r| ends.contains(&(r % period)));
+                continue;
+            }
+            let gcd = ext_gcd(modulus as i128, period as i128).0 as u128;
+            let Some(next) = (modulus / gcd).checked_mul(period).filter(|&m| m <= i128::MAX as u128) else {
+                // the combined modulus won't fit, so step through what the ghosts so far allow
+                // and check the rest directly
+                return first_step(&residues, modulus, settled, &ghosts[i..]);
+            };
+            residues = residues.iter()
+                .flat_map(|&a| ends.iter().filter_map(move |&r| crt((a, modulus), (r, period))))
+                .map(|(r, _)| r)
+                .collect();
+            modulus = next;
+        }
+        first_step(&residues, modulus, settled, &[])
+    }
 }
 
-impl Traversal {
-    fn new(node: &Node) -> Self {
-        Self {
-            node: *node,
-            ends: vec![],
-            repeat: None,
+/// The first step from `settled` onwards which is congruent to one of the `residues` (mod
+/// `modulus`), and where each of the `ghosts` is on an end node.
+fn first_step(residues: &[u128], modulus: u128, settled: u128, ghosts: &[GhostCycle]) -> Result<Option<u128>, String> {
+    if residues.is_empty() {
+        return Ok(None);
+    }
+    let mut steps = residues.iter()
+        .filter_map(|&r| settled.checked_add((r + modulus - settled % modulus) % modulus))
+        .collect::<Vec<_>>();
+    steps.sort();
+    // the steps all lie within one modulus of each other, so moving each on by the modulus keeps
+    // them in order
+    while !steps.is_empty() {
+        if let Some(&step) = steps.iter().find(|&&step| ghosts.iter().all(|g| g.is_end_at(step))) {
+            return Ok(Some(step));
         }
+        steps = steps.into_iter().filter_map(|step| step.checked_add(modulus)).collect();
     }
+    Err("the ghosts don't line up before the step count overflows".into())
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
-struct Node([u8; 3]);
-
-impl TryFrom<&[u8]> for Node {
-    type Error = String;
-    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
-        if bytes.len() != 3 {
-            Err("invalid length".into())
-        } else {
-            Ok(Node(bytes.try_into().unwrap()))
-        }
+/// Combines `x = a.0 (mod a.1)` and `x = b.0 (mod b.1)` into a single congruence modulo their
+/// lcm (which must fit in an `i128`), or `None` if they conflict.
+fn crt(a: (u128, u128), b: (u128, u128)) -> Option<(u128, u128)> {
+    let (g, p, _) = ext_gcd(a.1 as i128, b.1 as i128);
+    let diff = b.0 as i128 - a.0 as i128;
+    if diff % g != 0 {
+        return None;
     }
+    let lcm = a.1 / g as u128 * b.1;
+    let step = b.1 / g as u128;
+    // a.0 + a.1 * k, where k solves a.1 * k = diff (mod b.1)
+    let k = mul_mod((diff / g).rem_euclid(step as i128) as u128, p.rem_euclid(step as i128) as u128, step);
+    Some(((a.0 + a.1 * k) % lcm, lcm))
 }
 
-impl TryFrom<&str> for Node {
-    type Error = String;
-    fn try_from(s: &str) -> Result<Self, Self::Error> {
-        if s.len() != 3 {
-            Err(format!("invalid node length of {}", s.len()))
-        } else {
-            let node = s.as_bytes().try_into().unwrap();
-            if s.chars().any(|ch| !ch.is_ascii()) {
-                Err("non-ascii node character".into())
-            } else {
-                Ok(node)
-            }
-        }
+/// `a * b % m` (for `m` which fits in an `i128`), without overflowing along the way.
+fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    match a.checked_mul(b) {
+        Some(n) => n % m,
+        // double and add, one bit of `b` at a time
+        None => (0..128).rev().fold(0, |n, bit| match b >> bit & 1 {
+            1 => (n * 2 % m + a % m) % m,
+            _ => n * 2 % m,
+        }),
     }
 }
 
-impl std::fmt::Debug for Node {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let chars = self.0.map(|b| b as char);
-        write!(f, "{}", String::from_iter(chars))
+/// Returns (gcd, x, y) such that a * x + b * y = gcd
+fn ext_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    match b {
+        0 => (a, 1, 0),
+        _ => {
+            let (g, x, y) = ext_gcd(b, a % b);
+            (g, y, x - (a / b) * y)
+        },
     }
 }
 
-impl Node {
-    fn is_start(&self) -> bool {
-        self.0[2] == b'A'
+/// Moves every ghost one step at a time until they all line up (or `limit` steps pass).
+#[cfg(test)]
+fn brute_force(network: &Network, start_suffix: &str, end_suffix: &str, limit: u128) -> Option<u128> {
+    let mut nodes = (0..network.names.len())
+        .filter(|&n| network.names[n].ends_with(start_suffix))
+        .collect::<Vec<_>>();
+    for step in 0..limit {
+        if nodes.iter().all(|&n| network.names[n].ends_with(end_suffix)) {
+            return Some(step);
+        }
+        let branch = network.instructions[step as usize % network.instructions.len()];
+        nodes.iter_mut().for_each(|n| *n = network.branches[*n][branch]);
     }
+    None
+}
 
-    fn is_end(&self) -> bool {
-        self.0[2] == b'Z'
+#[test]
+fn general_networks() {
+    log_init();
+    // long names, a third branch, and ghosts which only line up after a while
+    for (example, expected) in [("example4", Some(5)), ("example5", None)] {
+        let network = Network::parse(&get_input(get_day!(), example), "LRS").unwrap();
+        assert_eq!(network.solve(|n| n.ends_with("start"), |n| n.ends_with("end")), Ok(expected));
+        assert_eq!(brute_force(&network, "start", "end", 10_000), expected);
     }
+    assert!(Network::parse(&Input::from("LR\n\nAAA = (BBB, CCC)"), "LR").is_err());
+    assert!(Network::parse(&Input::from("LR\n\nAAA = BBB"), "LR").is_err());
+    assert!(Network::parse(&Input::from("LX\n\nAAA = (AAA, AAA)"), "LR").is_err());
+
+    assert_eq!(crt((2, 3), (3, 5)), Some((8, 15)));
+    assert_eq!(crt((0, 4), (1, 6)), None);
+    // 2^200 = 2^73 * 2^127, which is 2^73 mod 2^127 - 1
+    assert_eq!(mul_mod(1 << 100, 1 << 100, i128::MAX as u128), 1 << 73);
 }
 
+#[test]
+fn overflowing_periods() {
+    log_init();
+    // ghosts on prime length cycles, whose periods multiply to more than fits in a u128, but
+    // which all reach an end node 5 steps in
+    let mut map = String::from("L\n");
+    for (g, len) in [32771, 32779, 32783, 32789, 32797, 32801, 32803, 32831, 32833].into_iter().enumerate() {
+        let name = |i: usize| match i {
+            0 => format!("{g}x0A"),
+            5 => format!("{g}x5Z"),
+            _ => format!("{g}x{i}"),
+        };
+        for i in 0..len {
+            let next = name((i + 1) % len);
+            map += &format!("\n{} = ({next}, {next})", name(i));
+        }
+    }
+    let network = Network::parse(&Input::from(map.as_str()), "LR").unwrap();
+    assert_eq!(network.solve(|n| n.ends_with('A'), |n| n.ends_with('Z')), Ok(Some(5)));
+
+    // if the ghosts reach their end nodes at different steps, there's no telling whether they'd
+    // ever line up past the largest step there is
+    let ghosts = network.ghosts(|n| n.ends_with('A'), |n| n.ends_with('Z'));
+    let shifted = ghosts.iter()
+        .enumerate()
+        .map(|(i, g)| GhostCycle { ends: vec![g.ends[0] + i], ..g.clone() })
+        .collect::<Vec<_>>();
+    assert!(first_step(&[5], i128::MAX as u128, 0, &shifted).is_err());
+}
 
 testcase!(ex1, solve_part1, "example", 2);
 testcase!(ex2, solve_part1, "example2", 6);
 testcase!(part1, solve_part1, "input", 16343);
 testcase!(ex3, solve_part2, "example3", 6);
 testcase!(part2, solve_part2, "input", 15299095336639);
+testcase!(general1, solve_general, "input", Ok(Some(16343)), "LR", "AAA", "ZZZ");
+testcase!(general2, solve_general, "input", Ok(Some(15299095336639)), "LR", "A", "Z");
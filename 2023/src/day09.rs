@@ -119,32 +119,188 @@ use super::*;
 /// If you find the next value for each history in this example and add them together, you get 114.
 ///
 /// Analyze your OASIS report and extrapolate the next value for each history. What is the sum of these extrapolated values?
-pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let all_seqs = input.into_iter().map(|s|
-        s.split_whitespace()
-            .map(|n| n.parse::<AdvInt>().expect("not a number"))
-            .collect::<Vec<_>>());
-
-    let mut sum = 0;
-    for seq in all_seqs {
-        let mut seqs = vec![seq];
-        while !seqs.iter().last().unwrap().iter().all(|x| *x == 0) {
-            seqs.push(diff(seqs.iter().last().unwrap()));
+pub fn solve_part1(input: &Input) -> AdvInt {
+    solve_extrapolated(input, 1).unwrap()
+}
+
+type AdvInt = i128;
+
+/// A history's values as a polynomial in Newton's forward-difference form: the value `x` steps
+/// after the first reading is the sum of each `differences[k] * C(x, k)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    /// the first value of each sequence of differences, down to the last non-zero one
+    differences: Vec<AdvInt>,
+    /// how many values the polynomial was fit to
+    len: usize,
+}
+
+impl Polynomial {
+    /// Fits the lowest degree polynomial to `values`. At least one sequence of differences has to
+    /// be all zeroes, so only histories of a degree below `values.len() - 1` can be fit.
+    pub fn fit(values: &[AdvInt]) -> Result<Self, String> {
+        let mut seq = values.to_vec();
+        let mut differences = vec![];
+        while !seq.iter().all(|&v| v == 0) {
+            if seq.len() == 1 {
+                return Err(format!(
+                    "{} values don't fit a polynomial of degree below {}",
+                    values.len(), values.len() - 1,
+                ));
+            }
+            differences.push(seq[0]);
+            seq = seq.windows(2)
+                .map(|s| s[1].checked_sub(s[0]).ok_or("difference overflows"))
+                .collect::<Result<_, _>>()?;
+        }
+        if values.is_empty() {
+            return Err("can't fit an empty history".into());
+        }
+
+        Ok(Self { differences, len: values.len() })
+    }
+
+    /// The degree of the polynomial (where the zero polynomial has none).
+    pub fn degree(&self) -> Option<usize> {
+        self.differences.len().checked_sub(1)
+    }
+
+    /// The value `x` steps after the first reading (which may be negative).
+    pub fn at(&self, x: AdvInt) -> Result<AdvInt, String> {
+        let overflow = || format!("value at {x} overflows");
+        let mut value: AdvInt = 0;
+        // C(x, k), built up from C(x, k - 1) * (x - k + 1) / k, which always divides exactly
+        let mut binomial: AdvInt = 1;
+        for (k, &d) in self.differences.iter().enumerate() {
+            if k > 0 {
+                binomial = binomial.checked_mul(x - k as AdvInt + 1).ok_or_else(overflow)? / k as AdvInt;
+            }
+            value = d.checked_mul(binomial)
+                .and_then(|term| value.checked_add(term))
+                .ok_or_else(overflow)?;
+        }
+        Ok(value)
+    }
+
+    /// The value `steps` readings after the last one, or before the first when negative.
+    pub fn extrapolate(&self, steps: isize) -> Result<AdvInt, String> {
+        match steps >= 0 {
+            true => self.at(self.len as AdvInt - 1 + steps as AdvInt),
+            false => self.at(steps as AdvInt),
+        }
+    }
+
+    /// The coefficients of each power of `x` (where `x = 0` is the first reading), starting with
+    /// the constant.
+    pub fn coefficients(&self) -> Result<Vec<Rational>, String> {
+        let overflow = || String::from("coefficient overflows");
+        let mut coefficients = vec![Rational::from(0); self.differences.len()];
+        // the falling factorial x (x - 1) ... (x - k + 1), and k!
+        let mut falling: Vec<AdvInt> = vec![1];
+        let mut factorial: AdvInt = 1;
+        for (k, &d) in self.differences.iter().enumerate() {
+            if k > 0 {
+                let root = k as AdvInt - 1;
+                let mut next: Vec<AdvInt> = vec![0; falling.len() + 1];
+                for (power, &c) in falling.iter().enumerate() {
+                    next[power + 1] = next[power + 1].checked_add(c).ok_or_else(overflow)?;
+                    next[power] = c.checked_mul(root)
+                        .and_then(|c| next[power].checked_sub(c))
+                        .ok_or_else(overflow)?;
+                }
+                falling = next;
+                factorial = factorial.checked_mul(k as AdvInt).ok_or_else(overflow)?;
+            }
+            for (power, &c) in falling.iter().enumerate() {
+                let term = Rational::new(d.checked_mul(c).ok_or_else(overflow)?, factorial);
+                coefficients[power] = coefficients[power].checked_add(term).ok_or_else(overflow)?;
+            }
+        }
+        Ok(coefficients)
+    }
+
+    /// Writes the polynomial in the usual form, e.g. `1/3x^3 - x^2 + 11/3x + 10`.
+    pub fn describe(&self) -> Result<String, String> {
+        let coefficients = self.coefficients()?;
+        let mut out = String::new();
+        let mut first = true;
+        for (power, c) in coefficients.iter().enumerate().rev().filter(|(_, c)| c.num != 0) {
+            let sign = match (first, c.num < 0) {
+                (true, false) => "",
+                (true, true) => "-",
+                (false, false) => " + ",
+                (false, true) => " - ",
+            };
+            let abs = Rational { num: c.num.abs(), den: c.den };
+            let abs = match (power, abs == Rational::from(1)) {
+                (0, _) => abs.to_string(),
+                (_, true) => String::new(),
+                (_, false) => abs.to_string(),
+            };
+            out += &match power {
+                0 => format!("{sign}{abs}"),
+                1 => format!("{sign}{abs}x"),
+                _ => format!("{sign}{abs}x^{power}"),
+            };
+            first = false;
+        }
+        if first {
+            out += "0";
         }
+        Ok(out)
+    }
+}
+
+/// An exact fraction, always kept in lowest terms with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rational {
+    pub num: AdvInt,
+    pub den: AdvInt,
+}
 
-        sum += seqs.iter()
-            .rev()
-            .map(|s| s.last().unwrap())
-            .sum::<AdvInt>();
+impl Rational {
+    fn new(num: AdvInt, den: AdvInt) -> Self {
+        let g = gcd(num, den) * den.signum();
+        Self { num: num / g, den: den / g }
     }
 
-    sum
+    fn checked_add(self, other: Self) -> Option<Self> {
+        let g = gcd(self.den, other.den);
+        let den = (self.den / g).checked_mul(other.den)?;
+        let num = self.num.checked_mul(den / self.den)?
+            .checked_add(other.num.checked_mul(den / other.den)?)?;
+        Some(Self::new(num, den))
+    }
+}
+
+impl From<AdvInt> for Rational {
+    fn from(n: AdvInt) -> Self {
+        Self { num: n, den: 1 }
+    }
+}
+
+impl std::fmt::Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.den {
+            1 => write!(f, "{}", self.num),
+            _ => write!(f, "{}/{}", self.num, self.den),
+        }
+    }
 }
 
-type AdvInt = isize;
+fn gcd(a: AdvInt, b: AdvInt) -> AdvInt {
+    match b {
+        0 => a.abs(),
+        _ => gcd(b, a % b),
+    }
+}
 
-fn diff(seq: &[AdvInt]) -> Vec<AdvInt> {
-    seq.windows(2).map(|s| s[1] - s[0]).collect()
+fn parse_histories(input: &Input) -> Result<Vec<Vec<AdvInt>>, String> {
+    input.lines()
+        .map(|s| parse::ints(s)
+            .map(|n| n.map_err(|e| format!("bad reading: {e}")))
+            .collect())
+        .collect()
 }
 
 
@@ -178,31 +334,54 @@ fn diff(seq: &[AdvInt]) -> Vec<AdvInt> {
 /// Analyze your OASIS report again, this time extrapolating the previous value for each history.
 /// What is the sum of these extrapolated values?
 ///
-pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let all_seqs = input.into_iter().map(|s|
-        s.split_whitespace()
-            .map(|n| n.parse::<AdvInt>().expect("not a number"))
-            .collect::<Vec<_>>());
-
-    let mut sum = 0;
-    for seq in all_seqs {
-        let mut seqs = vec![seq];
-        while !seqs.iter().last().unwrap().iter().all(|x| *x == 0) {
-            seqs.push(diff(seqs.iter().last().unwrap()));
-        }
+pub fn solve_part2(input: &Input) -> AdvInt {
+    solve_extrapolated(input, -1).unwrap()
+}
 
-        sum += seqs.iter()
-            .rev()
-            .map(|s| s[0])
-            .reduce(|acc, v| v - acc)
-            .unwrap();
-    }
+/// Sums each history's value `steps` readings past its end (or before its start, if negative).
+pub fn solve_extrapolated(input: &Input, steps: isize) -> Result<AdvInt, String> {
+    parse_histories(input)?
+        .iter()
+        .enumerate()
+        .map(|(i, values)| Polynomial::fit(values)
+            .and_then(|p| p.extrapolate(steps))
+            .map_err(|e| format!("history {}: {e}", i + 1)))
+        .try_fold(0 as AdvInt, |sum, v| sum.checked_add(v?).ok_or("sum overflows".into()))
+}
 
-    sum
+/// Writes out the polynomial behind each history, one per line.
+pub fn describe_histories(input: &Input) -> Result<String, String> {
+    parse_histories(input)?
+        .iter()
+        .map(|values| Polynomial::fit(values)?.describe())
+        .collect::<Result<Vec<_>, _>>()
+        .map(|lines| lines.join("\n"))
+}
+
+#[test]
+fn polynomials() {
+    log_init();
+    let p = Polynomial::fit(&[10, 13, 16, 21, 30, 45]).unwrap();
+    assert_eq!(p.degree(), Some(3));
+    assert_eq!(p.describe(), Ok("1/3x^3 - x^2 + 11/3x + 10".into()));
+    assert_eq!(p.extrapolate(1), Ok(68));
+    assert_eq!(p.extrapolate(-1), Ok(5));
+    assert_eq!(p.extrapolate(3), Ok(146));
+    assert_eq!(p.extrapolate(-3), Ok(-19));
+    assert_eq!(p.at(-1_000_000), Ok(-333334333336999990));
+
+    assert_eq!(Polynomial::fit(&[7, 7, 7]).unwrap().describe(), Ok("7".into()));
+    assert_eq!(Polynomial::fit(&[0, 0]).unwrap().degree(), None);
+    assert!(Polynomial::fit(&[1, 2, 4, 8, 16]).is_err());
+    assert!(Polynomial::fit(&[3]).is_err());
+    assert!(Polynomial::fit(&[]).is_err());
+    assert!(Polynomial::fit(&[0, 1, 4, 9]).unwrap().at(AdvInt::MAX / 2).is_err());
 }
 
 testcase!(ex1, solve_part1, "example", 114);
 testcase!(part1, solve_part1, "input", 1953784198);
 testcase!(ex2, solve_part2, "example", 2);
 testcase!(part2, solve_part2, "input", 957);
-
+testcase!(ahead2, solve_extrapolated, "example", Ok(21 + 36 + 101), 2);
+testcase!(behind3, solve_extrapolated, "example", Ok(-9 + 1 - 19), -3);
+testcase!(describe_ex, describe_histories, "example", Ok("3x\n1/2x^2 + 3/2x + 1\n1/3x^3 - x^2 + 11/3x + 10".into()));
@@ -150,9 +150,9 @@ use super::*;
 ///
 /// Find the single giant loop starting at S. How many steps along the loop does it take to get
 /// from the starting position to the point farthest from the starting position?
-pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let mut maze = PipeMaze::from(input);
-    maze.traverse()
+pub fn solve_part1(input: &Input) -> Result<AdvInt, String> {
+    let mut maze = PipeMaze::parse(input)?;
+    Ok(maze.traverse())
 }
 
 /// # Enclosed Area
@@ -279,47 +279,77 @@ pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
 /// Figure out whether you have time to search for the nest by calculating the area within the
 /// loop. How many tiles are enclosed by the loop?
 ///
-pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let mut maze = PipeMaze::from(input);
+pub fn solve_part2(input: &Input) -> Result<AdvInt, String> {
+    let mut maze = PipeMaze::parse(input)?;
     maze.traverse();
+    Ok(maze.enclosed_by_crossings())
+}
 
-    debug!(">>>>> Traverse done, performing longitudinal collision detection' <<<<<");
-    let mut enclosed = 0;
-    for y in 0..(maze.flatmap.len() / maze.width) as isize {
-        // for each line, keep track of whether we've entered or exited the loop
-        // by determining which way the loop entered and exited our line
-        let mut crossed: Crossed = false.into();
-        let mut inside = false;
-        debug!("Examining Line: {y}");
-        for x in 0..maze.width as isize {
-            // if we crossed over the loop completely after leaving the last tile, we're either
-            // gone from inside to outside, or outside to inside.
-            if crossed.over() {
-                warn!("CROSSED");
-                inside = !inside;
-                crossed = false.into();
-            }
-            // if we're on a loop tile, record which ways the pipe is connected
-            if maze.get(x, y).depth.is_some() {
-                let dirs = maze.connected_dirs(Coord { x, y });
-                crossed.north ^= dirs.contains(&North);
-                crossed.south ^= dirs.contains(&South);
-                debug!("Pipe {dirs:?} => {crossed:?}");
-            } else {
-                // if we're not on a loop tile, forget any directions were crossed
-                crossed = false.into();
-
-                if inside {
-                    info!(" -> TILE INSIDE");
-                    enclosed += 1
-                } else {
-                    debug!("<-  tile outside");
-                }
-            }
-        }
+/// Solves part 2 independently of [`solve_part2`]: the shoelace formula gives the area of the
+/// loop's polygon, and Pick's theorem turns that area into the number of tiles inside it.
+pub fn solve_part2_area(input: &Input) -> Result<AdvInt, String> {
+    let mut maze = PipeMaze::parse(input)?;
+    maze.traverse();
+    Ok(maze.enclosed_by_area())
+}
+
+/// Solves part 2 both ways, failing if they disagree.
+pub fn solve_part2_checked(input: &Input) -> Result<AdvInt, String> {
+    let mut maze = PipeMaze::parse(input)?;
+    maze.traverse();
+    let (crossings, area) = (maze.enclosed_by_crossings(), maze.enclosed_by_area());
+    if crossings == area {
+        Ok(area)
+    } else {
+        Err(format!("enclosed tiles disagree: {crossings} by crossings, {area} by area"))
     }
+}
+
+/// The corners of the main loop, in order around the loop.
+pub fn loop_polygon(input: &Input) -> Result<Vec<(isize, isize)>, String> {
+    let mut maze = PipeMaze::parse(input)?;
+    maze.traverse();
+    Ok(maze.polygon()
+        .into_iter()
+        .map(|c| (c.x, c.y))
+        .collect())
+}
+
+/// The main loop drawn as an SVG polygon, one unit per tile.
+pub fn loop_svg(input: &Input) -> Result<String, String> {
+    let mut maze = PipeMaze::parse(input)?;
+    maze.traverse();
+    Ok(maze.polygon_svg())
+}
+
+/// Finds every loop and every dead-end stretch of pipe in the maze, whether or not it has a start.
+pub fn analyze_maze(input: &Input) -> Result<MazeReport, String> {
+    Ok(PipeMaze::parse(input)?.analyze())
+}
 
-    enclosed
+/// Everything [`analyze_maze`] found in a maze.
+#[derive(Debug, PartialEq, Eq)]
+pub struct MazeReport {
+    /// the pipe hidden under `S` (if there is one, and its neighbors give it a consistent shape)
+    pub start: Option<char>,
+    /// every closed loop, in reading order of their first tile
+    pub loops: Vec<LoopReport>,
+    /// the tiles of each connected stretch of pipe which doesn't close into a loop, in reading
+    /// order
+    pub dead_ends: Vec<Vec<(isize, isize)>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct LoopReport {
+    /// every tile in the loop in order, starting from `S` if it's on the loop, or otherwise from
+    /// the loop's first tile in reading order
+    pub tiles: Vec<(isize, isize)>,
+    pub length: usize,
+    /// the tile farthest along the loop from the first tile, and how far that is
+    pub farthest: (isize, isize),
+    pub distance: usize,
+    /// the number of tiles inside the loop
+    pub enclosed: usize,
 }
 
 /// Numeric type used for the answer to this puzzle
@@ -327,8 +357,6 @@ type AdvInt = usize;
 
 #[derive(Copy, Clone, Eq, PartialEq)]
 struct Pipe {
-    // Some(depth) from start pipe. None when not seen in traversal.
-    depth: Option<AdvInt>,
     kind: u8,
 }
 
@@ -348,6 +376,18 @@ impl Pipe {
     }
 }
 
+impl Pipe {
+    /// the kind of pipe connecting the two directions
+    fn shape(a: Direction, b: Direction) -> u8 {
+        [b'|', b'-', b'L', b'J', b'7', b'F'].into_iter()
+            .find(|&kind| {
+                let dirs = Pipe::from(kind).dirs();
+                dirs.contains(&a) && dirs.contains(&b)
+            })
+            .expect("no pipe connects a direction to itself")
+    }
+}
+
 impl From<u8> for Pipe {
     fn from(byte: u8) -> Pipe {
         match byte {
@@ -357,8 +397,8 @@ impl From<u8> for Pipe {
           | b'J'
           | b'7'
           | b'F'
-          | b'.' => Pipe { kind: byte, depth: None },
-            b'S' => Pipe { kind: byte, depth: Some(0) },
+          | b'.'
+          | b'S' => Pipe { kind: byte },
             _ => panic!("invalid pipe character: {}", byte as char),
         }
     }
@@ -426,62 +466,60 @@ impl std::fmt::Debug for Coord {
 }
 
 
+#[derive(Clone)]
 struct PipeMaze {
     width: usize,
     flatmap: Vec<Pipe>,
+    /// how far every pipe connected to the start is from it, once traversed
+    reached: graph::Paths<Coord, AdvInt>,
+    /// every tile of the loop through the start, in order, once traversed
+    main_loop: Vec<Coord>,
 }
 
 impl PipeMaze {
-    fn from<L: IntoIterator<Item = String>>(lines: L) -> PipeMaze {
-        let (mut width, mut height) = (0, 0);
-        let mut flatmap = vec![];
-
-        let lines = lines.into_iter().map(|l| {
-            l.as_bytes()
-                .to_owned()
-                .iter()
-                .map(|&b| Pipe::from(b))
-                .collect::<Vec<Pipe>>()
-        });
-        for mut line in lines {
-            width = width.max(line.len());
-            debug_assert_eq!(width, line.len()); // double-check for even map widths
-            height += 1;
-            flatmap.append(&mut line);
-        }
-        // double-check that flattened grid dimensions match 2D grid
-        debug_assert_eq!(height * width, flatmap.len());
+    /// Reads the maze, which has to be rectangular.
+    fn parse(input: &Input) -> Result<PipeMaze, String> {
+        let grid = input.grid()?;
+        let width = grid.width();
+        let flatmap = grid.rows()
+            .flat_map(|row| row.iter().map(|&b| Pipe::from(b)))
+            .collect::<Vec<_>>();
 
-        // double-check that we only have a single start-pipe
-        debug_assert_eq!(flatmap.iter().filter(|&&p| p.kind == b'S').count(), 1);
+        // double-check that we don't have more than one start-pipe
+        debug_assert!(flatmap.iter().filter(|&&p| p.kind == b'S').count() <= 1);
 
         debug!("the map:");
-        for y in 0..height {
-            let line = (0..width).map(|x| flatmap[y * width + x].kind as char).collect::<String>();
-            debug!("  {}", line);
+        for row in grid.rows() {
+            debug!("  {}", String::from_utf8_lossy(row));
         }
 
-        PipeMaze {
+        Ok(PipeMaze {
             width,
             flatmap,
-        }
+            reached: Default::default(),
+            main_loop: vec![],
+        })
     }
 
-    fn get(&self, x: isize, y: isize) -> Pipe {
-        if x < 0 || y < 0 {
-            return Pipe::from(b'.');
+    /// Where (`x`, `y`) is in the flat map, if it's on the map at all. Past the end of a row
+    /// doesn't wrap around onto the next one.
+    fn index(&self, x: isize, y: isize) -> Option<usize> {
+        if x < 0 || y < 0 || x as usize >= self.width {
+            return None;
         }
+        Some(y as usize * self.width + x as usize).filter(|&i| i < self.flatmap.len())
+    }
 
-        let i = (y as usize * self.width) + x as usize;
-        match self.flatmap.get(i) {
-            Some(&p) => p,
-            _ => Pipe::from(b'.'),
+    fn get(&self, x: isize, y: isize) -> Pipe {
+        match self.index(x, y) {
+            Some(i) => self.flatmap[i],
+            None => Pipe::from(b'.'),
         }
     }
 
     fn try_get_mut(&mut self, x: isize, y: isize) -> Option<&mut Pipe> {
-        let i = (y as usize * self.width) + x as usize;
-        self.flatmap.get_mut(i)
+        let i = self.index(x, y)?;
+        Some(&mut self.flatmap[i])
     }
 
     fn at(&self, coord: Coord) -> Pipe {
@@ -493,43 +531,43 @@ impl PipeMaze {
     }
 
     fn start(&self) -> Coord {
-        let index = self.flatmap.iter()
-            .enumerate()
-            .find(|(_, p)| p.kind == b'S')
-            .expect("no start in maze")
-            .0;
+        self.find_start().expect("no start in maze")
+    }
+
+    fn find_start(&self) -> Option<Coord> {
+        let index = self.flatmap.iter().position(|p| p.kind == b'S')?;
+        Some(self.coord_of(index))
+    }
+
+    fn coord_of(&self, index: usize) -> Coord {
         Coord {
             x: (index % self.width) as isize,
             y: (index / self.width) as isize,
         }
     }
 
-    fn connecting(&self, coord: Coord) -> Vec<Coord> {
+    /// The tiles which the pipe at `coord` connects to (and which connect back).
+    fn links(&self, coord: Coord) -> Vec<Coord> {
         self.at(coord)
             .dirs()
             .into_iter()
-            .filter_map(|dir| {
-                let next = coord + dir.into();
-                let here = self.at(coord);
-                let there = self.at(next);
-                debug!("Checking: {:?} at {:?} ({:?}) going {:?} = {:?} at {:?} ({:?})", here, coord, here.depth, dir, there, next, there.depth);
-                if there.depth.is_none() && self.can_connect(coord, dir, next) {
-                    info!("Connection: {:?} -> {:?}", coord, next);
-                    Some(next)
-                } else {
-                    None
-                }
-            })
+            .filter(|&dir| self.can_connect(coord, dir, coord + dir.into()))
+            .map(|dir| coord + dir.into())
+            .inspect(|next| debug!("Connection: {:?} at {coord:?} -> {:?} at {next:?}", self.at(coord), self.at(*next)))
             .collect()
     }
 
+    /// Whether `coord` was reached from the start when traversing.
+    fn on_loop(&self, coord: Coord) -> bool {
+        self.reached.contains(&coord)
+    }
+
     fn can_connect(&self, from: Coord, dir: Direction, to: Coord) -> bool {
         self.at(from).dirs().contains(&dir) && self.at(to).dirs().contains(&dir.rev())
     }
 
     fn connected_dirs(&self, coord: Coord) -> Vec<Direction> {
-        let here = self.at(coord);
-        if here.depth.is_none() {
+        if !self.on_loop(coord) {
             return vec![];
         }
 
@@ -538,39 +576,207 @@ impl PipeMaze {
             .into_iter()
             .filter(|&dir| {
                 let neighbor = coord + dir.into();
-                self.at(neighbor).depth.is_some() && self.can_connect(coord, dir, neighbor)
+                self.on_loop(neighbor) && self.can_connect(coord, dir, neighbor)
             })
             .collect()
     }
 
-    /// Traverses the pipes, setting depth (distance from start) on all pipes visited.
+    /// Traverses the pipes breadth-first from the start, returning the distance to the farthest.
     fn traverse(&mut self) -> usize {
         let start = self.start();
+        self.reached = graph::bfs([start], |&coord| self.links(coord));
+        debug!("Reached {} pipes from the start", self.reached.reached().len());
+
+        // walk the loop once more to record it in order
+        self.main_loop = self.at(start).dirs()
+            .into_iter()
+            .find_map(|dir| self.follow(start, dir))
+            .unwrap_or_default();
+        debug!("Main loop is {} tiles long", self.main_loop.len());
 
-        let mut paths = self.connecting(start);
-        let mut depth = 1;
-        while !paths.is_empty() {
-            debug!("==> DEPTH: {depth}");
+        self.reached.farthest().map_or(0, |(_, depth)| depth)
+    }
 
-            // visit each current coordinate in the path
-            for coord in &paths {
-                let here = self.try_at_mut(*coord).unwrap();
-                here.depth = Some(depth);
-                warn!("{:?} at {:?} is now seen with depth {:?}", self.at(*coord), coord, self.at(*coord).depth);
+    /// Follows the pipes leaving `start` heading `dir`, returning every tile passed through (in
+    /// order, from `start`) if they lead back around to `start`.
+    fn follow(&self, start: Coord, dir: Direction) -> Option<Vec<Coord>> {
+        let mut tiles = vec![start];
+        let (mut here, mut heading) = (start, dir);
+        loop {
+            let next = here + heading.into();
+            if !self.can_connect(here, heading, next) {
+                return None;
+            }
+            if next == start {
+                return Some(tiles);
             }
+            tiles.push(next);
+            // leave through the pipe's other opening
+            heading = self.at(next).dirs().into_iter().find(|&d| d != heading.rev())?;
+            here = next;
+        }
+    }
 
-            // for each coordinate, find non-traversed connecting coordinates and "traverse" them
-            paths = paths.into_iter()
-                .flat_map(|coord| self.connecting(coord))
-                .collect::<Vec<_>>();
+    /// Counts the tiles inside the main loop by scanning each line, flipping between inside and
+    /// outside whenever the loop is crossed.
+    fn enclosed_by_crossings(&self) -> usize {
+        debug!(">>>>> Traverse done, performing longitudinal collision detection' <<<<<");
+        let mut enclosed = 0;
+        for y in 0..(self.flatmap.len() / self.width) as isize {
+            // for each line, keep track of whether we've entered or exited the loop
+            // by determining which way the loop entered and exited our line
+            let mut crossed: Crossed = false.into();
+            let mut inside = false;
+            debug!("Examining Line: {y}");
+            for x in 0..self.width as isize {
+                // if we crossed over the loop completely after leaving the last tile, we're either
+                // gone from inside to outside, or outside to inside.
+                if crossed.over() {
+                    warn!("CROSSED");
+                    inside = !inside;
+                    crossed = false.into();
+                }
+                // if we're on a loop tile, record which ways the pipe is connected
+                if self.on_loop(Coord { x, y }) {
+                    let dirs = self.connected_dirs(Coord { x, y });
+                    crossed.north ^= dirs.contains(&North);
+                    crossed.south ^= dirs.contains(&South);
+                    debug!("Pipe {dirs:?} => {crossed:?}");
+                } else {
+                    // if we're not on a loop tile, forget any directions were crossed
+                    crossed = false.into();
+
+                    if inside {
+                        info!(" -> TILE INSIDE");
+                        enclosed += 1
+                    } else {
+                        debug!("<-  tile outside");
+                    }
+                }
+            }
+        }
+
+        enclosed
+    }
+
+    /// Counts the tiles inside the main loop with the shoelace formula and Pick's theorem.
+    fn enclosed_by_area(&self) -> usize {
+        let area = shoelace(&self.main_loop);
+        // Pick's theorem: area = inside + boundary / 2 - 1
+        let inside = (area + 2).saturating_sub(self.main_loop.len() as isize) / 2;
+        debug!("Loop area: {}, boundary: {}, inside: {inside}", area as f64 / 2.0, self.main_loop.len());
+        inside as usize
+    }
 
-            depth += 1;
+    /// Works out which pipe the start tile must be from the pipes connecting into it, preferring a
+    /// shape which closes a loop.
+    fn infer_start(&self) -> Option<u8> {
+        let start = self.find_start()?;
+        let open = [North, South, East, West].into_iter()
+            .filter(|&dir| self.at(start + dir.into()).dirs().contains(&dir.rev()))
+            .collect::<Vec<_>>();
+        let shapes = open.iter()
+            .enumerate()
+            .flat_map(|(i, &a)| open[(i + 1)..].iter().map(move |&b| (a, b)))
+            .map(|(a, b)| (a, Pipe::shape(a, b)))
+            .collect::<Vec<_>>();
+
+        let closes_loop = |&(dir, kind): &(Direction, u8)| {
+            let mut maze = self.clone();
+            maze.try_at_mut(start).unwrap().kind = kind;
+            maze.follow(start, dir).is_some()
+        };
+        shapes.iter()
+            .find(|shape| closes_loop(shape))
+            .or(shapes.first())
+            .map(|&(_, kind)| kind)
+    }
+
+    /// Splits every pipe into connected pieces, and reports on each of them. The start is first
+    /// replaced by its inferred pipe (or ground, if it can't be inferred).
+    fn analyze(&self) -> MazeReport {
+        let start = self.infer_start();
+        let mut maze = self.clone();
+        if let Some(coord) = self.find_start() {
+            maze.try_at_mut(coord).unwrap().kind = start.unwrap_or(b'.');
+        }
+
+        let pipes = (0..maze.flatmap.len())
+            .filter(|&index| maze.flatmap[index].kind != b'.')
+            .map(|index| maze.coord_of(index));
+        let mut loops = vec![];
+        let mut dead_ends = vec![];
+        for mut piece in graph::components(pipes, |&coord| maze.links(coord)) {
+            // every pipe on a loop connects both ways
+            let closed = piece.iter().all(|&coord| maze.links(coord).len() == 2);
+            if !closed {
+                piece.sort_by_key(|c| (c.y, c.x));
+                dead_ends.push(piece.into_iter().map(|c| (c.x, c.y)).collect());
+                continue;
+            }
+
+            // walk the loop in order, from the start if it's on this loop
+            let first = self.find_start()
+                .filter(|s| piece.contains(s))
+                .unwrap_or(piece[0]);
+            let dir = maze.at(first).dirs()[0];
+            maze.main_loop = maze.follow(first, dir).expect("a closed piece is a loop");
+            let tiles = &maze.main_loop;
+            let distance = tiles.len() / 2;
+            loops.push(LoopReport {
+                tiles: tiles.iter().map(|c| (c.x, c.y)).collect(),
+                length: tiles.len(),
+                farthest: (tiles[distance].x, tiles[distance].y),
+                distance,
+                enclosed: maze.enclosed_by_area(),
+            });
+        }
+
+        MazeReport {
+            start: start.map(|kind| kind as char),
+            loops,
+            dead_ends,
         }
+    }
+
+    /// Only the tiles of the main loop where it turns a corner.
+    fn polygon(&self) -> Vec<Coord> {
+        let tiles = &self.main_loop;
+        (0..tiles.len())
+            .filter(|&i| {
+                let prev = tiles[(i + tiles.len() - 1) % tiles.len()];
+                let next = tiles[(i + 1) % tiles.len()];
+                prev.x != next.x && prev.y != next.y
+            })
+            .map(|i| tiles[i])
+            .collect()
+    }
 
-        self.flatmap.iter().fold(0, |acc, p| p.depth.map_or(acc, |d| d.max(acc)))
+    fn polygon_svg(&self) -> String {
+        let height = self.flatmap.len() / self.width;
+        let points = self.polygon()
+            .iter()
+            .map(|c| format!("{},{}", c.x, c.y))
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!(concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-0.5 -0.5 {} {}">"#,
+            r#"<polygon points="{}" fill="lightgreen" stroke="black" stroke-width="0.2"/>"#,
+            "</svg>"), self.width, height, points)
     }
 }
 
+/// Twice the area of the polygon through `points` (twice, so it stays a whole number).
+fn shoelace(points: &[Coord]) -> isize {
+    (0..points.len())
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<isize>()
+        .abs()
+}
+
 #[derive(Debug, Clone, Copy, Default)]
 struct Crossed { north: bool, south: bool }
 
@@ -586,11 +792,66 @@ impl From<bool> for Crossed {
     }
 }
 
-testcase!(ex1, solve_part1, "example1", 4);
-testcase!(ex2, solve_part1, "example2", 4);
-testcase!(ex3, solve_part1, "example3", 8);
-testcase!(part1, solve_part1, "input", 6842);
-testcase!(ex4, solve_part2, "example4", 4);
-testcase!(ex5, solve_part2, "example5", 4);
-testcase!(ex6, solve_part2, "example6", 8);
-testcase!(part2, solve_part2, "input", 393);
+#[test]
+fn analyze_ex() -> Result<(), String> {
+    log_init();
+    // S is still a 90-degree F bend
+    let report = analyze_maze(&get_input(get_day!(), "example1"))?;
+    assert_eq!(report.start, Some('F'));
+    assert_eq!(report.loops.len(), 1);
+    assert_eq!(report.loops[0].length, 8);
+    assert_eq!(report.loops[0].farthest, (3, 3));
+    assert_eq!(report.loops[0].distance, 4);
+    assert_eq!(report.loops[0].enclosed, 1);
+    assert!(report.dead_ends.is_empty());
+
+    // the same loop surrounded by junk pipes
+    let report = analyze_maze(&get_input(get_day!(), "example2"))?;
+    assert_eq!(report.start, Some('F'));
+    assert_eq!(report.loops.iter().map(|l| l.length).collect::<Vec<_>>(), [8]);
+    assert!(report.dead_ends.iter().all(|d| !d.contains(&(1, 1))));
+    assert_eq!(report.dead_ends.iter().map(|d| d.len()).sum::<usize>(), 25 - 8);
+
+    // several loops with no start at all
+    let maze = Input::from(".F7.F-7\n.LJ.|.|\nF7..L-J\nLJ.-...\n");
+    let report = analyze_maze(&maze)?;
+    assert_eq!(report.start, None);
+    assert_eq!(report.loops.iter().map(|l| (l.length, l.enclosed)).collect::<Vec<_>>(), [(4, 0), (8, 1), (4, 0)]);
+    assert_eq!(report.dead_ends, [vec![(3, 3)]]);
+
+    // pipes which point off the right edge don't wrap around onto the next row
+    let report = analyze_maze(&Input::from("F-\n-J\n"))?;
+    assert_eq!(report.dead_ends, [vec![(0, 0), (1, 0)], vec![(0, 1), (1, 1)]]);
+    let report = analyze_maze(&Input::from("..F-\n-7|.\n.LJ.\n"))?;
+    assert!(report.loops.is_empty());
+    assert_eq!(report.dead_ends, [vec![(2, 0), (3, 0), (0, 1), (1, 1), (2, 1), (1, 2), (2, 2)]]);
+
+    // and the real thing agrees with both parts
+    let report = analyze_maze(&get_input(get_day!(), "input"))?;
+    let start = PipeMaze::parse(&get_input(get_day!(), "input"))?.start();
+    let main = report.loops.iter().find(|l| l.tiles[0] == (start.x, start.y)).unwrap();
+    assert_eq!((main.distance, main.enclosed), (6842, 393));
+
+    // the maze has to be rectangular
+    assert_eq!(analyze_maze(&Input::from("S7\nL")).err(), Some("row 1 is 1 wide, but row 0 is 2".into()));
+    Ok(())
+}
+
+testcase!(ex1, solve_part1, "example1", Ok(4));
+testcase!(ex2, solve_part1, "example2", Ok(4));
+testcase!(ex3, solve_part1, "example3", Ok(8));
+testcase!(part1, solve_part1, "input", Ok(6842));
+testcase!(ex4, solve_part2, "example4", Ok(4));
+testcase!(ex5, solve_part2, "example5", Ok(4));
+testcase!(ex6, solve_part2, "example6", Ok(8));
+testcase!(part2, solve_part2, "input", Ok(393));
+testcase!(area4, solve_part2_area, "example4", Ok(4));
+testcase!(area5, solve_part2_area, "example5", Ok(4));
+testcase!(area6, solve_part2_area, "example6", Ok(8));
+testcase!(area2, solve_part2_area, "input", Ok(393));
+testcase!(checked, solve_part2_checked, "input", Ok(393));
+testcase!(polygon1, loop_polygon, "example1", Ok(vec![(1, 1), (1, 3), (3, 3), (3, 1)]));
+testcase!(svg1, loop_svg, "example1", Ok(concat!(
+    r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-0.5 -0.5 5 5">"#,
+    r#"<polygon points="1,1 1,3 3,3 3,1" fill="lightgreen" stroke="black" stroke-width="0.2"/>"#,
+    "</svg>").to_string()));
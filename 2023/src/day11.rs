@@ -127,7 +127,7 @@ use super::*;
 ///
 /// Expand the universe, then find the length of the shortest path between every pair of galaxies.
 /// What is the sum of these lengths?
-pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
+pub fn solve_part1(input: &Input) -> AdvInt {
     // part1 is just part2 but with a factor-of-2 expansion
     solve_part2(input, 2)
 }
@@ -149,109 +149,267 @@ pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
 /// Starting with the same initial image, expand the universe according to these new rules, then
 /// find the length of the shortest path between every pair of galaxies. What is the sum of these
 /// lengths?
-pub fn solve_part2<L: IntoIterator<Item = String>>(input: L, expansion_factor: usize) -> AdvInt {
-    let universe = Universe::from(input);
-
-    let empty_cols = universe.empty_cols();
-    let empty_rows = universe.empty_rows();
-
-    // calculate L1 ("taxicab geometry") distance for each pair of galaxies
-    let mut distances = vec![];
-    for i in 0..universe.galaxies.len() {
-        let from = &universe.galaxies[i];
-        for to in &universe.galaxies[(i+1)..] {
-            let mut dist = from.0.abs_diff(to.0) as AdvInt + from.1.abs_diff(to.1) as AdvInt;
-            debug!("   Distance (unexpanded) from {:?} to {:?} = {:?}", from, to, dist);
-
-            // find all empty rows and columns between this pair of galaxies and multiply each
-            // dimension of the L1 distance by those expanding rows/cols (times their factor)
-            let cols_between = if from.0 < to.0 { from.0 + 1 .. to.0 } else { to.0 + 1 .. from.0 };
-            let expanded_cols = empty_cols
-                .iter()
-                .filter(|&c| cols_between.contains(c))
-                .count()
-                * (expansion_factor - 1);
-            debug!("   Extra cols: {}", expanded_cols);
-
-            let rows_between = if from.1 < to.1 { from.1 + 1 .. to.1 } else { to.1 + 1 .. from.1 };
-            let expanded_rows = empty_rows
-                .iter()
-                .filter(|&r| rows_between.contains(r))
-                .count()
-                * (expansion_factor - 1);
-            debug!("   Extra rows: {}", expanded_cols);
-
-            dist += expanded_cols + expanded_rows;
-            debug!("-> Full Distance from {:?} to {:?} = {:?}", from, to, dist);
-            distances.push(dist);
-        }
-    }
+pub fn solve_part2(input: &Input, expansion_factor: u64) -> AdvInt {
+    solve_separate(input, expansion_factor, expansion_factor)
+}
 
-    distances.into_iter().sum()
+/// Like [`solve_part2`], but with empty columns and empty rows expanding by different factors.
+pub fn solve_separate(input: &Input, col_factor: u64, row_factor: u64) -> AdvInt {
+    let universe = Universe::from(input);
+    universe.expand(col_factor, row_factor).total()
 }
 
-type AdvInt = usize;
+type AdvInt = u128;
 
-struct Universe {
-    map: Vec<u8>,
+pub struct Universe {
     width: usize,
     height: usize,
-    galaxies: Vec<(i32, i32)>,
+    /// (column, row) of each galaxy, in reading order
+    galaxies: Vec<(usize, usize)>,
 }
 
 impl Universe {
-    fn from<L: IntoIterator<Item = String>>(lines: L) -> Self {
-        let mut width = 0;
-        let mut height = 0;
-        let map = lines.into_iter()
-            .flat_map(|s| {
-                width = width.max(s.len());
-                height += 1;
-                s.as_bytes().to_vec()
-            })
-            .collect::<Vec<u8>>();
-
-        // verify correct flattening of the 2D map
-        debug_assert_eq!(width * height, map.len());
-
-        let galaxies = map
-            .iter()
+    pub fn from(input: &Input) -> Self {
+        // rows can be ragged, so the universe is as wide as the longest
+        let galaxies = input.byte_lines()
             .enumerate()
-            .filter(|(_, &b)| b == b'#')
-            .map(|(i, _)| ((i % width) as i32, (i / width) as i32))
+            .flat_map(|(row, line)| line.iter()
+                .enumerate()
+                .filter(|&(_, &b)| b == b'#')
+                .map(move |(col, _)| (col, row)))
             .collect();
 
         Universe {
-            map,
-            width,
-            height,
+            width: input.byte_lines().map(<[u8]>::len).max().unwrap_or(0),
+            height: input.lines().count(),
             galaxies,
         }
     }
 
-    fn empty_rows(&self) -> Vec<i32> {
-        (0..self.height)
-            .filter(|&row| {
-                self.map.iter()
-                    .enumerate()
-                    .filter(|(offset, _)| offset / self.width == row)
-                    .all(|(_, &x)| x == b'.')
-            })
-            .map(|row| row as i32)
-            .collect()
+    /// The (column, row) of each galaxy before expansion, numbered in reading order from 0.
+    pub fn galaxies(&self) -> &[(usize, usize)] {
+        &self.galaxies
+    }
+
+    /// Expands each empty column into `col_factor` columns, and each empty row into `row_factor`
+    /// rows.
+    pub fn expand(&self, col_factor: u64, row_factor: u64) -> Expanded {
+        let (mut cols, mut rows) = (vec![col_factor; self.width], vec![row_factor; self.height]);
+        for &(col, row) in &self.galaxies {
+            cols[col] = 1;
+            rows[row] = 1;
+        }
+        self.place(&cols, &rows)
+    }
+
+    /// Expands the universe so that every column and row is as wide as its given weight, whether
+    /// or not it's empty.
+    pub fn expand_weighted(&self, col_weights: &[u64], row_weights: &[u64]) -> Result<Expanded, String> {
+        if col_weights.len() != self.width {
+            return Err(format!("expected {} column weights, got {}", self.width, col_weights.len()));
+        }
+        if row_weights.len() != self.height {
+            return Err(format!("expected {} row weights, got {}", self.height, row_weights.len()));
+        }
+        Ok(self.place(col_weights, row_weights))
+    }
+
+    /// Positions each galaxy given one weight per column and per row.
+    fn place(&self, col_weights: &[u64], row_weights: &[u64]) -> Expanded {
+        let (cols, rows) = (prefix_sums(col_weights), prefix_sums(row_weights));
+        Expanded {
+            positions: self.galaxies.iter()
+                .map(|&(col, row)| (cols[col], rows[row]))
+                .collect(),
+        }
+    }
+}
+
+/// The positions of each galaxy after expansion, in the same order as [`Universe::galaxies`].
+pub struct Expanded {
+    positions: Vec<(u128, u128)>,
+}
+
+impl Expanded {
+    pub fn positions(&self) -> &[(u128, u128)] {
+        &self.positions
+    }
+
+    /// The L1 ("taxicab geometry") distance between two galaxies.
+    pub fn distance(&self, from: usize, to: usize) -> u128 {
+        let (from, to) = (self.positions[from], self.positions[to]);
+        from.0.abs_diff(to.0) + from.1.abs_diff(to.1)
+    }
+
+    /// The sum of the distances between every pair of galaxies.
+    pub fn total(&self) -> u128 {
+        // the L1 distance splits into independent sums along each axis
+        let (cols, rows): (Vec<_>, Vec<_>) = self.positions.iter().copied().unzip();
+        let (col_sum, row_sum) = (sum_pairwise(cols), sum_pairwise(rows));
+        debug!("Column distances: {col_sum}, row distances: {row_sum}");
+        col_sum + row_sum
     }
 
-    fn empty_cols(&self) -> Vec<i32> {
-        (0..self.width)
-            .filter(|&col| {
-                self.map.iter()
-                    .enumerate()
-                    .filter(|(offset, _)| offset % self.width == col)
-                    .all(|(_, &x)| x == b'.')
-            })
-            .map(|col| col as i32)
+    /// For each galaxy, the closest other galaxy and its distance (or `None` if it's alone). Ties go
+    /// to the lowest-numbered galaxy.
+    pub fn nearest(&self) -> Vec<Option<(usize, u128)>> {
+        // sweep outwards along the columns, stopping once the columns alone are too far away
+        let mut by_col = (0..self.positions.len()).collect::<Vec<_>>();
+        by_col.sort_unstable_by_key(|&i| self.positions[i]);
+
+        let mut nearest = vec![None; self.positions.len()];
+        for (rank, &i) in by_col.iter().enumerate() {
+            let col = self.positions[i].0;
+            let mut best: Option<(usize, u128)> = None;
+            // returns false once no galaxy further along this side can be any closer
+            let mut consider = |j: usize| {
+                if best.is_some_and(|(_, dist)| self.positions[j].0.abs_diff(col) > dist) {
+                    return false;
+                }
+                let dist = self.distance(i, j);
+                if best.is_none_or(|(k, d)| (dist, j) < (d, k)) {
+                    best = Some((j, dist));
+                }
+                true
+            };
+            for &j in by_col[..rank].iter().rev() {
+                if !consider(j) {
+                    break;
+                }
+            }
+            for &j in &by_col[(rank + 1)..] {
+                if !consider(j) {
+                    break;
+                }
+            }
+            nearest[i] = best;
+        }
+        nearest
+    }
+
+    /// For each galaxy, one of the farthest other galaxies and its distance (or `None` if it's
+    /// alone).
+    pub fn farthest(&self) -> Vec<Option<(usize, u128)>> {
+        // rotating by 45 degrees turns L1 distance into the largest difference along either axis,
+        // so the farthest galaxy is always one of the extremes of those axes
+        let rotated = |i: usize| {
+            let (col, row) = (self.positions[i].0 as i128, self.positions[i].1 as i128);
+            [col + row, col - row]
+        };
+        let count = self.positions.len();
+        let extremes = (0..2)
+            .flat_map(|axis| [
+                (0..count).min_by_key(|&i| rotated(i)[axis]),
+                (0..count).max_by_key(|&i| rotated(i)[axis]),
+            ])
+            .flatten()
+            .collect::<Vec<_>>();
+
+        (0..count).map(|i| {
+            extremes.iter()
+                .filter(|&&j| j != i)
+                .map(|&j| (j, self.distance(i, j)))
+                .max_by_key(|&(j, dist)| (dist, std::cmp::Reverse(j)))
+        }).collect()
+    }
+
+    /// The distance between every pair of galaxies.
+    pub fn distance_matrix(&self) -> Vec<Vec<u128>> {
+        (0..self.positions.len())
+            .map(|i| (0..self.positions.len()).map(|j| self.distance(i, j)).collect())
             .collect()
     }
+
+    /// The distance matrix as CSV, with galaxies numbered from 1 like the puzzle does.
+    pub fn distance_matrix_csv(&self) -> String {
+        let count = self.positions.len();
+        let mut csv = String::from("galaxy");
+        for j in 1..=count {
+            csv += &format!(",{j}");
+        }
+        for (i, row) in self.distance_matrix().into_iter().enumerate() {
+            csv += &format!("\n{}", i + 1);
+            for dist in row {
+                csv += &format!(",{dist}");
+            }
+        }
+        csv
+    }
+}
+
+/// Gives the expanded position of every index along an axis: a running (prefix) sum of the width
+/// of everything before it.
+fn prefix_sums(weights: &[u64]) -> Vec<u128> {
+    let mut pos = 0;
+    weights.iter()
+        .map(|&weight| {
+            let start = pos;
+            pos += weight as u128;
+            start
+        }).collect()
+}
+
+/// Sums the distance between every pair of positions along one axis in O(n log n): once sorted,
+/// the `i`th position is `i * pos - (sum of the i before it)` away from all of those before it.
+fn sum_pairwise(mut positions: Vec<u128>) -> u128 {
+    positions.sort_unstable();
+    let mut prefix = 0;
+    positions.into_iter()
+        .enumerate()
+        .map(|(i, pos)| {
+            let dist = i as u128 * pos - prefix;
+            prefix += pos;
+            dist
+        }).sum()
+}
+
+#[test]
+fn pairwise_ex() {
+    log_init();
+    let universe = Universe::from(&get_input(get_day!(), "example"));
+    let expanded = universe.expand(2, 2);
+    assert_eq!(expanded.distance(4, 8), 9);
+    assert_eq!(expanded.distance(0, 6), 15);
+    assert_eq!(expanded.distance(2, 5), 17);
+    assert_eq!(expanded.distance(7, 8), 5);
+
+    // against a brute-force search over the matrix
+    let matrix = expanded.distance_matrix();
+    let brute = |pick: fn(u128, u128) -> bool| (0..matrix.len())
+        .map(|i| (0..matrix.len())
+            .filter(|&j| j != i)
+            .fold(None, |best: Option<(usize, u128)>, j| match best {
+                Some((_, d)) if !pick(matrix[i][j], d) => best,
+                _ => Some((j, matrix[i][j])),
+            }))
+        .collect::<Vec<_>>();
+    assert_eq!(expanded.nearest(), brute(|a, b| a < b));
+    // (there's more than one farthest galaxy in places, so only compare the distances)
+    let distances = |found: Vec<Option<(usize, u128)>>| found.into_iter()
+        .map(|f| f.map(|(_, dist)| dist))
+        .collect::<Vec<_>>();
+    assert_eq!(distances(expanded.farthest()), distances(brute(|a, b| a > b)));
+    assert_eq!(matrix.iter().flatten().sum::<u128>(), 2 * expanded.total());
+
+    let csv = expanded.distance_matrix_csv();
+    assert_eq!(csv.lines().next(), Some("galaxy,1,2,3,4,5,6,7,8,9"));
+    assert_eq!(csv.lines().nth(5), Some("5,9,13,5,8,0,12,12,6,9"));
+}
+
+#[test]
+fn weighted_ex() {
+    log_init();
+    let universe = Universe::from(&get_input(get_day!(), "example"));
+    // uniform weights of 1 (no expansion) or 2 (everything doubled)
+    assert_eq!(universe.expand_weighted(&[1; 10], &[1; 10]).map(|e| e.total()), Ok(292));
+    assert_eq!(universe.expand_weighted(&[2; 10], &[2; 10]).map(|e| e.total()), Ok(2 * 292));
+    assert!(universe.expand_weighted(&[1; 9], &[1; 10]).is_err());
+    assert!(universe.expand_weighted(&[1; 10], &[1; 11]).is_err());
+    assert_eq!(universe.expand(2, 2).total(), 374);
+
+    // rows don't all have to be the same length
+    let ragged = Universe::from(&Input::from("#\n..#\n"));
+    assert_eq!(ragged.expand(2, 2).distance(0, 1), 4);
 }
 
 testcase!(ex1, solve_part1, "example", 374);
@@ -259,3 +417,19 @@ testcase!(part1, solve_part1, "input", 9795148);
 testcase!(ex2, solve_part2, "example", 1030, 10);
 testcase!(ex3, solve_part2, "example", 8410, 100);
 testcase!(part2, solve_part2, "input", 650672493820, 1_000_000);
+testcase!(unexpanded, solve_separate, "example", 292, 1, 1);
+testcase!(cols_only, solve_separate, "example", 706, 10, 1);
+testcase!(rows_only, solve_separate, "example", 616, 1, 10);
+testcase!(huge, solve_part2, "example", 292 + 82 * (u64::MAX as u128 - 1), u64::MAX);
+
+#[test]
+fn many_galaxies() {
+    log_init();
+    // every third cell is a galaxy, so there are no empty rows or columns to expand
+    let input = Input::from((0..600)
+        .map(|row| (0..600).map(|col| if (row + col) % 3 == 0 { '#' } else { '.' }).collect::<String>() + "\n")
+        .collect::<String>());
+    let universe = Universe::from(&input);
+    assert_eq!(universe.galaxies.len(), 120_000);
+    assert_eq!(solve_part2(&input, u64::MAX), solve_part2(&input, 2));
+}
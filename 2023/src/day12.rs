@@ -119,15 +119,15 @@ type AdvInt = usize;
 ///
 /// For each row, count all of the different arrangements of operational and broken springs that
 /// meet the given criteria. What is the sum of those counts?
-pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let input: Vec<String> = input.into_iter().collect();
+pub fn solve_part1(input: &Input) -> AdvInt {
+    let input: Vec<&str> = input.lines().collect();
     input.par_iter()
         .enumerate()
         .map(|(i, line)| {
             let record: Vec<&str> = line.split_whitespace().collect();
             let (row, groups) = (record[0].as_bytes(), record[1]);
             let groups: Vec<usize> = groups.split(',').map(|n| n.parse().unwrap()).collect();
-            let count = count_fits(&row, &groups);
+            let count = count_fits(row, &groups);
             info!("Row: {i:3} sum = {count}");
             count
         }).sum()
@@ -174,140 +174,194 @@ pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
 /// After unfolding, adding all of the possible arrangement counts together produces 525152.
 ///
 /// Unfold your condition records; what is the new sum of possible arrangement counts?
-pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let input: Vec<String> = input.into_iter().collect();
+pub fn solve_part2(input: &Input) -> u128 {
+    solve_unfolded(input, 5, "?")
+}
+
+/// Unfolds every record `factor` times, joining the copies of each row with `separator`, and sums
+/// the exact number of arrangements with [`count_unfolded`].
+pub fn solve_unfolded(input: &Input, factor: usize, separator: &str) -> u128 {
+    let input: Vec<&str> = input.lines().collect();
     input.par_iter()
         .enumerate()
         .map(|(i, line)| {
             let record: Vec<&str> = line.split_whitespace().collect();
             let (row, groups) = (record[0].as_bytes(), record[1]);
-            let doublerow = [row, b"?", row].concat();
             let groups: Vec<usize> = groups.split(',').map(|n| n.parse().unwrap()).collect();
-            let doublegroups = groups.repeat(2);
-            let count = count_fits(&row, &groups);
-            let doublecount = count_fits(&doublerow, &doublegroups);
-            let factor = doublecount / count;
-            let triplerow = [row, b"?", row, b"?", row].concat();
-            let triplegroups = groups.repeat(3);
-            let triplecount = count_fits(&triplerow, &triplegroups);
-            if triplecount == doublecount * factor {
-                let count = doublecount * factor * factor * factor;
-                info!("Row: {i:3} sum = {count}");
-                count
-            } else {
-                let count = count_fits(&[row, b"?", row, b"?", row, b"?", row, b"?", row ].concat(), &groups.repeat(5));
-                error!("Row: {i:3} sum = {count} (MANUAL)");
-                count
+            let count = count_unfolded(row, &groups, factor, separator.as_bytes())
+                .unwrap_or_else(|e| panic!("row {i}: {e}"));
+            info!("Row: {i:3} sum = {count}");
+            count
+        }).reduce(|| 0, |acc, count| acc.checked_add(count).expect("total overflows u128"))
+}
+
+/// Counts the arrangements of a record after unfolding it `factor` times: the row is repeated with
+/// `separator` between each copy, and the groups are repeated alongside it.
+pub fn count_unfolded(row: &[u8], groups: &[usize], factor: usize, separator: &[u8]) -> Result<u128, String> {
+    let row = vec![row; factor].join(separator);
+    count_fits_dp(&row, &groups.repeat(factor))
+}
+
+/// Counts the arrangements of `groups` within `line` exactly, with a dynamic program over (position,
+/// group index). Unlike [`count_fits`], this takes O(len * groups) time no matter how the row is
+/// laid out, and reports an error rather than overflowing.
+pub fn count_fits_dp(line: &[u8], groups: &[usize]) -> Result<u128, String> {
+    // same trailing gap as count_fits, so every group is followed by a gap
+    let line = [line, b"."].concat();
+    let len = line.len();
+
+    // number of cells from each position until the next definite gap
+    let mut open_run = vec![0; len + 1];
+    for i in (0..len).rev() {
+        open_run[i] = if line[i] == b'.' { 0 } else { open_run[i + 1] + 1 };
+    }
+
+    // ways[p]: ways to place the groups so far, with the last one's gap ending just before p
+    let mut ways = vec![0u128; len + 1];
+    ways[0] = 1;
+
+    for &piece in groups {
+        let mut next = vec![0u128; len + 1];
+        // ways to be sitting at the current start, having skipped only non-springs to get there
+        let mut reachable = 0u128;
+        for start in 0..len {
+            reachable = reachable.checked_add(ways[start])
+                .ok_or("arrangement count overflows u128")?;
+            if reachable != 0 && open_run[start] >= piece && start + piece < len
+                && line[start + piece] != b'#'
+            {
+                next[start + piece + 1] = reachable;
             }
-        }).sum()
+            // a spring can't be skipped over
+            if line[start] == b'#' {
+                reachable = 0;
+            }
+        }
+        ways = next;
+    }
+
+    // any spring after the last group would be unaccounted for
+    let first_valid_end = line.iter().rposition(|&b| b == b'#').map_or(0, |i| i + 1);
+    ways[first_valid_end..].iter()
+        .try_fold(0u128, |acc, &w| acc.checked_add(w))
+        .ok_or_else(|| "arrangement count overflows u128".into())
+}
+
+/// Lazily walks every concrete row (like `.###.##.#...`) which fits the groups into a record, in
+/// the same order as the arrangements are listed in the puzzle. Being an [`Iterator`], any page of
+/// arrangements is just a `skip().take()` away, and a sample a `step_by()`.
+pub struct Arrangements<'a> {
+    /// the record, with the same trailing gap as [`count_fits`]
+    line: Vec<u8>,
+    groups: &'a [usize],
+    /// where each group placed so far starts
+    starts: Vec<usize>,
+    /// the first position to try placing the next group at
+    next: usize,
+    done: bool,
+}
+
+impl<'a> Arrangements<'a> {
+    pub fn new(row: &[u8], groups: &'a [usize]) -> Self {
+        Self {
+            line: [row, b"."].concat(),
+            groups,
+            starts: vec![],
+            next: 0,
+            done: false,
+        }
+    }
+
+    /// Whether a group of size `piece` (and the gap after it) can go at `start`.
+    fn fits(&self, start: usize, piece: usize) -> bool {
+        start + piece < self.line.len()
+            && !self.line[start..(start + piece)].contains(&b'.')
+            && self.line[start + piece] != b'#'
+    }
+
+    /// Moves the most recently placed group along by one, or finishes if there are none left.
+    fn backtrack(&mut self) {
+        match self.starts.pop() {
+            Some(start) => self.next = start + 1,
+            None => self.done = true,
+        }
+    }
+
+    fn draw(&self) -> String {
+        let mut row = vec![b'.'; self.line.len() - 1];
+        for (&start, &piece) in self.starts.iter().zip(self.groups) {
+            row[start..(start + piece)].fill(b'#');
+        }
+        String::from_utf8(row).unwrap()
+    }
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        while !self.done {
+            let placed = self.starts.len();
+            // the first position after the previous group's gap
+            let floor = match placed {
+                0 => 0,
+                _ => self.starts[placed - 1] + self.groups[placed - 1] + 1,
+            };
+
+            if placed == self.groups.len() {
+                // every group is placed, so any spring left over is unaccounted for
+                let found = (!self.line[floor..].contains(&b'#')).then(|| self.draw());
+                self.backtrack();
+                if found.is_some() {
+                    return found;
+                }
+                continue;
+            }
+
+            let piece = self.groups[placed];
+            let needed = self.groups[placed..].iter().sum::<usize>() + self.groups.len() - placed;
+            // can't skip over a spring, or leave too little room for the rest
+            let last = self.line[floor..].iter()
+                .position(|&b| b == b'#')
+                .map_or(self.line.len(), |i| floor + i)
+                .min(self.line.len().saturating_sub(needed));
+
+            match (self.next.max(floor)..=last).find(|&start| self.fits(start, piece)) {
+                Some(start) => {
+                    self.starts.push(start);
+                    self.next = start + piece + 1;
+                },
+                None => self.backtrack(),
+            }
+        }
+        None
+    }
+}
+
+/// Draws a record with the given arrangements aligned underneath it, one per line.
+pub fn render<I: IntoIterator<Item = String>>(row: &str, groups: &[usize], arrangements: I) -> String {
+    let groups = groups.iter().map(|g| g.to_string()).collect::<Vec<_>>().join(",");
+    let mut drawing = format!("{row} {groups}");
+    for arrangement in arrangements {
+        drawing.push('\n');
+        drawing.push_str(&arrangement);
+    }
+    drawing
 }
 
-//fn perms(groups: &[usize], len: usize) -> Vec<Vec<usize>> {
-//    inner_perms(groups, len, 0)
-//}
-//fn inner_perms(groups: &[usize], len: usize, depth: usize) -> Vec<Vec<usize>> {
-//    debug!("{0:1$}inner_perms(): len = {len:2} | {groups:?}", "", 4*depth);
-//    // no groups, no remaining length ==> no permutations
-//    if len == 0 || groups.len() == 0 {
-//        return vec![];
-//    }
-//    // minimum amount of length required to fill the rest of `len`
-//    let min_len = groups.iter().sum::<usize>() + (groups.len() - 1);
-//    if min_len > len {
-//        return vec![];
-//    }
-//
-//    if groups.len() == 1 {
-//        let remaining = len.saturating_sub(groups[0]);
-//        return (0..=remaining).map(|p| vec![p]).collect();
-//    } else {
-//        // groups.len() > 1, so apply recursion
-//        let remaining = len.saturating_sub(groups[0] + 1);
-//        let mut perms = vec![];
-//        for i in 0.. {
-//            let inner = inner_perms(&groups[1..], remaining.saturating_sub(i), depth + 1);
-//            debug!("{0:1$}==> {inner:?}", "", 4*(depth+1));
-//            match inner.len() {
-//                0 => break,
-//                _ => {
-//                    let mut this = inner.into_iter().map(|p| [vec![i], p].concat()).collect();
-//                    perms.append(&mut this);
-//                },
-//            }
-//        }
-//        perms
-//    }
-//}
-//
-//fn draw_perm(groups: &[usize], len: usize, perm: &[usize]) -> String {
-//    debug_assert_eq!(groups.len(), perm.len());
-//    let mut drawn = 0;
-//    let mut drawing = String::new();
-//    for i in 0..groups.len() {
-//        for _ in 0..(perm[i]) {
-//            drawing.push('.');
-//            drawn += 1;
-//        }
-//        for _ in 0..(groups[i]) {
-//            drawing.push('#');
-//            drawn += 1;
-//        }
-//        // all springs except the last must be followed by a space
-//        if i != groups.len() - 1 {
-//            drawing.push('.');
-//            drawn += 1;
-//        }
-//    }
-//    for _ in 0..(len.saturating_sub(drawn)) {
-//        drawing.push('.');
-//    }
-//    //debug!("{}", drawing);
-//    drawing
-//}
-//
-//fn draw_perms(groups: &[usize], len: usize, perms: &[Vec<usize>]) {
-//    for perm in perms {
-//        draw_perm(&groups, len, perm);
-//    }
-//}
-//
-//fn can_perm_fit(line: &str, permstr: &str) -> bool {
-//    let line_bytes = line.as_bytes();
-//    let perm_bytes = permstr.as_bytes();
-//
-//    if line_bytes.len() != perm_bytes.len() {
-//        return false;
-//    }
-//
-//    for (&l, &p) in line_bytes.iter().zip(perm_bytes.iter()) {
-//        match (l, p) {
-//            (b'#', b'.') => return false,
-//            (b'.', b'#') => return false,
-//            (_, _) => continue,
-//        }
-//    }
-//
-//    return true;
-//}
-//
-//fn count_arrangements(line: &str, groups: Vec<usize>) -> usize {
-//    let len = line.len();
-//    let perms = perms(&groups, len);
-//
-//    let mut fits = 0;
-//    for perm in perms {
-//        let permstr = draw_perm(&groups, len, &perm);
-//        let can = can_perm_fit(&line, &permstr);
-//        if can {
-//            debug!("{permstr} <-- FITS");
-//            debug!("{line} <-- (...in here)");
-//            debug!("");
-//            fits += 1;
-//        }
-//    }
-//
-//    fits
-//}
+/// Renders one page of arrangements under each record, `per_page` at a time.
+pub fn draw_arrangements(input: &Input, page: usize, per_page: usize) -> String {
+    input.lines()
+        .map(|line| {
+            let (row, groups) = line.split_once(' ').unwrap();
+            let groups: Vec<usize> = groups.split(',').map(|n| n.parse().unwrap()).collect();
+            let arrangements = Arrangements::new(row.as_bytes(), &groups)
+                .skip(page * per_page)
+                .take(per_page);
+            render(row, &groups, arrangements)
+        }).collect::<Vec<_>>()
+        .join("\n\n")
+}
 
 fn count_fits(line: &[u8], groups: &[usize]) -> AdvInt {
     // add a fake extra space to the end of the row to make the recursive problem uniform:
@@ -318,6 +372,7 @@ fn count_fits(line: &[u8], groups: &[usize]) -> AdvInt {
     count_fits_inner(&line, groups, 0)
 }
 
+#[allow(clippy::only_used_in_recursion)] // depth is only read by the debug output
 fn count_fits_inner(line: &[u8], groups: &[usize], depth: usize) -> AdvInt {
     let to_fit = groups.iter().sum::<usize>() + groups.len();
     let space_left = line.len();
@@ -327,7 +382,7 @@ fn count_fits_inner(line: &[u8], groups: &[usize], depth: usize) -> AdvInt {
 
     // no more pieces to fit?
     if to_fit == 0 {
-        if line.iter().any(|&b| b == b'#') {
+        if line.contains(&b'#') {
             //debug!("Spring slot missed, cannot place");
             return 0;
         } else {
@@ -379,77 +434,169 @@ fn count_fits_inner(line: &[u8], groups: &[usize], depth: usize) -> AdvInt {
     //}
 }
 
-//#[test]
-//fn permute_ex1() {
-//    log_init();
-//    let groups = vec![3, 2, 1];
-//    let len = 10;
-//    let p = perms(&groups, len);
-//    debug!("TESTING: {p:?}");
-//    draw_perms(&groups, len, &p);
-//}
-//
-//#[test]
-//fn permute_ex2() {
-//    log_init();
-//    let groups = vec![2, 5, 3, 4];
-//    let len = 23;
-//    let p = perms(&groups, len);
-//    debug!("TESTING: {p:?}");
-//    draw_perms(&groups, len, &p);
-//}
-//
-//#[test]
-//fn permute_fit1() {
-//    log_init();
-//    let line = String::from("???.###");
-//    let groups = [1,1,3];
-//    let len = line.len();
-//    let perms = perms(&groups, len);
-//
-//    let mut fits = 0;
-//    for perm in perms {
-//        let permstr = draw_perm(&groups, len, &perm);
-//        let can = can_perm_fit(&line, &permstr);
-//        if can {
-//            debug!("FITS!");
-//            fits += 1;
-//        } else {
-//            debug!(" doesnt fit");
-//        }
-//        debug!("     {permstr}");
-//        debug!("  in {line}");
-//        debug!("");
-//    }
-//
-//    debug_assert_eq!(fits, 1);
-//}
-//
-//#[test]
-//fn permute_fit2() {
-//    log_init();
-//    let line = String::from(".??..??...?##.");
-//    let groups = [1,1,3];
-//    let len = line.len();
-//    let perms = perms(&groups, len);
-//
-//    let mut fits = 0;
-//    for perm in perms {
-//        let permstr = draw_perm(&groups, len, &perm);
-//        let can = can_perm_fit(&line, &permstr);
-//        if can {
-//            debug!("FITS!");
-//            fits += 1;
-//        } else {
-//            debug!(" doesnt fit");
-//        }
-//        debug!("     {permstr}");
-//        debug!("  in {line}");
-//        debug!("");
-//    }
-//
-//    debug_assert_eq!(fits, 4);
-//}
+/// A 2-D paint-by-numbers puzzle: every row and column is its own spring record, with every cell
+/// initially unknown.
+pub struct Nonogram {
+    rows: Vec<Vec<usize>>,
+    cols: Vec<Vec<usize>>,
+}
+
+/// How many ways a [`Nonogram`] can be filled in. At most two solutions are found, enough to tell
+/// a unique solution apart from many.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Solutions {
+    None,
+    One(Picture),
+    Many(Picture, Picture),
+}
+
+/// A filled-in (or partially filled-in) nonogram, using the same `#`, `.` and `?` as the records.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Picture {
+    width: usize,
+    cells: Vec<u8>,
+}
+
+impl std::fmt::Display for Picture {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // a picture with no columns has no cells, and so nothing to draw
+        for (i, row) in self.cells.chunks(self.width.max(1)).enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", String::from_utf8_lossy(row))?;
+        }
+        Ok(())
+    }
+}
+
+impl Nonogram {
+    /// Reads the row and column clues, one line per row or column. Groups may be separated by
+    /// commas or spaces, and a blank line (or `0`) is a line with no filled cells.
+    pub fn from_clues<'a, R, C>(rows: R, cols: C) -> Self
+    where
+        R: IntoIterator<Item = &'a str>,
+        C: IntoIterator<Item = &'a str>,
+    {
+        fn parse_clue(line: &str) -> Vec<usize> {
+            line.split(|ch: char| ch == ',' || ch.is_whitespace())
+                .filter(|n| !n.is_empty())
+                .map(|n| n.parse().unwrap())
+                .filter(|&n| n != 0)
+                .collect()
+        }
+        Self {
+            rows: rows.into_iter().map(parse_clue).collect(),
+            cols: cols.into_iter().map(parse_clue).collect(),
+        }
+    }
+
+    pub fn solve(&self) -> Solutions {
+        let picture = Picture {
+            width: self.cols.len(),
+            cells: vec![b'?'; self.rows.len() * self.cols.len()],
+        };
+        let mut found = vec![];
+        self.search(picture, &mut found);
+
+        let mut found = found.into_iter();
+        match (found.next(), found.next()) {
+            (None, _) => Solutions::None,
+            (Some(one), None) => Solutions::One(one),
+            (Some(one), Some(two)) => Solutions::Many(one, two),
+        }
+    }
+
+    /// Propagates as far as possible, then guesses at the first unknown cell and backtracks.
+    fn search(&self, mut picture: Picture, found: &mut Vec<Picture>) {
+        if found.len() >= 2 || !self.propagate(&mut picture) {
+            return;
+        }
+        match picture.cells.iter().position(|&cell| cell == b'?') {
+            None => found.push(picture),
+            Some(i) => {
+                for guess in [b'#', b'.'] {
+                    debug!("Guessing {} at cell {i}", guess as char);
+                    let mut guessed = picture.clone();
+                    guessed.cells[i] = guess;
+                    self.search(guessed, found);
+                }
+            },
+        }
+    }
+
+    /// Solves lines one at a time until none of them change. Returns false on a contradiction.
+    fn propagate(&self, picture: &mut Picture) -> bool {
+        let (width, height) = (self.cols.len(), self.rows.len());
+        let mut dirty_rows = vec![true; height];
+        let mut dirty_cols = vec![true; width];
+
+        while dirty_rows.contains(&true) || dirty_cols.contains(&true) {
+            let rows = (0..height).filter(|&r| std::mem::take(&mut dirty_rows[r])).collect::<Vec<_>>();
+            for r in rows {
+                let mut line = picture.cells[(r * width)..((r + 1) * width)].to_vec();
+                if !solve_line(&mut line, &self.rows[r]) {
+                    return false;
+                }
+                for (c, &cell) in line.iter().enumerate() {
+                    if picture.cells[r * width + c] != cell {
+                        picture.cells[r * width + c] = cell;
+                        dirty_cols[c] = true;
+                    }
+                }
+            }
+            let cols = (0..width).filter(|&c| std::mem::take(&mut dirty_cols[c])).collect::<Vec<_>>();
+            for c in cols {
+                let mut line = (0..height).map(|r| picture.cells[r * width + c]).collect::<Vec<_>>();
+                if !solve_line(&mut line, &self.cols[c]) {
+                    return false;
+                }
+                for (r, &cell) in line.iter().enumerate() {
+                    if picture.cells[r * width + c] != cell {
+                        picture.cells[r * width + c] = cell;
+                        dirty_rows[r] = true;
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Fills in every unknown cell of a line which is the same in all of its arrangements. Returns
+/// false if the line has no arrangements at all.
+fn solve_line(line: &mut [u8], groups: &[usize]) -> bool {
+    // an overflowing count still means there's at least one arrangement
+    let fits = |line: &[u8]| count_fits_dp(line, groups) != Ok(0);
+
+    if !fits(line) {
+        return false;
+    }
+    for i in 0..line.len() {
+        if line[i] != b'?' {
+            continue;
+        }
+        line[i] = b'#';
+        let can_fill = fits(line);
+        line[i] = b'.';
+        let can_gap = fits(line);
+        line[i] = match (can_fill, can_gap) {
+            (true, false) => b'#',
+            (false, true) => b'.',
+            _ => b'?',
+        };
+    }
+    true
+}
+
+/// Solves the nonogram with the given row and column clues.
+pub fn solve_nonogram(rows: &Input, cols: &Input) -> String {
+    match Nonogram::from_clues(rows.lines(), cols.lines()).solve() {
+        Solutions::None => "no solutions".to_string(),
+        Solutions::One(picture) => format!("one solution:\n{picture}"),
+        Solutions::Many(one, two) => format!("many solutions, including:\n{one}\n\n{two}"),
+    }
+}
 
 #[ignore]
 #[test]
@@ -457,7 +604,7 @@ fn subslices() {
     log_init();
     fn recur(s: &[i32]) {
         debug!("s = {:?} | s.len() = {}", s, s.len());
-        if s.len() != 0 {
+        if !s.is_empty() {
             recur(&s[1..]);
         }
     }
@@ -474,14 +621,6 @@ fn debugpart1new1a() {
     assert_eq!(count_fits(line, &groups), 2);
 }
 
-//#[test]
-//fn debugpart1new1b() {
-//    log_init();
-//    let line = ".#?????####.?.#?";
-//    let groups = [1, 1, 5, 1];
-//    assert_eq!(count_arrangements(&line, groups.to_vec()), 2);
-//}
-
 #[ignore]
 #[test]
 fn debugex1() {
@@ -570,11 +709,117 @@ fn trysimplepart2e() {
 
 }
 
-//testcase!(tricky1, solve_part2, "tricky1", 275891715176);
-//testcase!(tricky2, solve_part2, "tricky2", 344867425584);
+#[test]
+fn arrangements_ex1() {
+    log_init();
+    let expected = "\
+?###???????? 3,2,1
+.###.##.#...
+.###.##..#..
+.###.##...#.
+.###.##....#
+.###..##.#..
+.###..##..#.
+.###..##...#
+.###...##.#.
+.###...##..#
+.###....##.#";
+    let groups = [3, 2, 1];
+    let arrangements = Arrangements::new(b"?###????????", &groups);
+    assert_eq!(render("?###????????", &groups, arrangements), expected);
+
+    // paging and sampling
+    let page = Arrangements::new(b"?###????????", &groups).skip(4).take(3).collect::<Vec<_>>();
+    assert_eq!(page, [".###..##.#..", ".###..##..#.", ".###..##...#"]);
+    let sample = Arrangements::new(b"?###????????", &groups).step_by(4).collect::<Vec<_>>();
+    assert_eq!(sample, [".###.##.#...", ".###..##.#..", ".###...##..#"]);
+}
+
+#[test]
+fn arrangements_agree() {
+    log_init();
+    for line in get_input(get_day!(), "input").lines() {
+        let (row, groups) = line.split_once(' ').unwrap();
+        let groups: Vec<usize> = groups.split(',').map(|n| n.parse().unwrap()).collect();
+        let count = count_fits(row.as_bytes(), &groups);
+        assert_eq!(Arrangements::new(row.as_bytes(), &groups).count(), count, "{line}");
+    }
+}
+
+#[test]
+fn dp_agrees() {
+    log_init();
+    for line in get_input(get_day!(), "input").lines() {
+        let (row, groups) = line.split_once(' ').unwrap();
+        let groups: Vec<usize> = groups.split(',').map(|n| n.parse().unwrap()).collect();
+        let count = count_fits(row.as_bytes(), &groups);
+        assert_eq!(count_fits_dp(row.as_bytes(), &groups), Ok(count as u128), "{line}");
+    }
+}
+
+#[test]
+fn dp_overflow() {
+    log_init();
+    assert_eq!(count_unfolded(b"?", &[], 200, b"?"), Ok(1));
+    assert_eq!(count_unfolded(b"???.###", &[1, 1, 3], 1000, b"?"), Ok(1));
+    assert!(count_unfolded(b"????????????", &[1, 1], 100, b"?").is_err());
+}
+
+testcase!(tricky1, solve_part2, "tricky1", 275891715176);
+testcase!(tricky2, solve_part2, "tricky2", 344867425584);
+
+testcase!(draw_ex, draw_arrangements, "example", "\
+???.### 1,1,3
+#.#.###
+
+.??..??...?##. 1,1,3
+.#...#....###.
+.#....#...###.
+
+?#?#?#?#?#?#?#? 1,3,1,6
+.#.###.#.######
+
+????.#...#... 4,1,1
+####.#...#...
+
+????.######..#####. 1,6,5
+#....######..#####.
+.#...######..#####.
+
+?###???????? 3,2,1
+.###.##.#...
+.###.##..#..", 0, 2);
+
+#[test]
+fn empty_nonogram() {
+    log_init();
+    let solved = Nonogram::from_clues(["", ""], []).solve();
+    assert_eq!(solved, Solutions::One(Picture { width: 0, cells: vec![] }));
+    assert_eq!(solve_nonogram(&Input::from("\n\n"), &Input::from("")), "one solution:\n");
+}
+
+testcase!(heart, solve_nonogram, "heart-rows", "\
+one solution:
+.##.##.
+#######
+#######
+.#####.
+..###..
+...#...
+.......", &get_input(get_day!(), "heart-cols"));
+testcase!(ambiguous, solve_nonogram, "ambiguous-rows", "\
+many solutions, including:
+#.
+.#
+
+.#
+#.", &get_input(get_day!(), "ambiguous-cols"));
+testcase!(impossible, solve_nonogram, "impossible-rows", "no solutions", &get_input(get_day!(), "impossible-cols"));
 
 testcase!(ex1, solve_part1, "example", 21);
 testcase!(part1, solve_part1, "input", 6949);
 testcase!(ex2, solve_part2, "example", 525152);
-
-//testcase!(part2, solve_part2, "input", 0);
+testcase!(part2, solve_part2, "input", 51456609952403);
+testcase!(unfold1, solve_unfolded, "example", 21, 1, "?");
+testcase!(unfold3, solve_unfolded, "example", 2612, 3, "?");
+testcase!(unfold_gap, solve_unfolded, "example", 3 + 2 * 4u128.pow(30) + 10u128.pow(30), 30, ".");
@@ -19,7 +19,6 @@
 
 
 use rayon::prelude::*;
-use std::collections::HashSet;
 use super::*;
 type AdvInt = usize;
 
@@ -101,12 +100,8 @@ type AdvInt = usize;
 ///
 /// Find the line of reflection in each of the patterns in your notes. What number do you get after
 /// summarizing all of your notes?
-pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    input.into_iter()
-        .collect::<Vec<String>>()
-        .split(|line| line.is_empty())
-        .map(|p| v_mirrors(p).first().unwrap_or(&0) + h_mirrors(p).first().unwrap_or(&0) * 100)
-        .sum()
+pub fn solve_part1(input: &Input) -> AdvInt {
+    solve_smudged(input, 0)
 }
 
 /// # Smudges
@@ -181,88 +176,239 @@ pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
 ///
 /// In each pattern, fix the smudge and find the different line of reflection. What number do you
 /// get after summarizing the new reflection line in each pattern in your notes?
-pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    input.into_iter()
-        .collect::<Vec<String>>()
-        .split(|line| line.is_empty())
-        .map(|l| l.to_vec())
-        .collect::<Vec<Vec<String>>>()
+pub fn solve_part2(input: &Input) -> AdvInt {
+    solve_smudged(input, 1)
+}
+
+/// Summarizes every reflection line which would be valid with exactly `smudges` smudges fixed.
+pub fn solve_smudged(input: &Input, smudges: u32) -> AdvInt {
+    input.blocks()
+        .map(|pat| Pattern::from(&pat[..]))
+        .collect::<Vec<Pattern>>()
         .par_iter()
-        .map(|pat| -> usize {
-            // calculate which mirrors we already had
-            let init_v = v_mirrors(pat);
-            let init_h = h_mirrors(pat);
-            let mut new_v = HashSet::<usize>::new();
-            let mut new_h = HashSet::<usize>::new();
+        .map(|pat| {
+            let v = mirrors(&pat.cols, smudges);
+            let h = mirrors(&pat.rows, smudges);
+            debug!("vertical mirrors: {v:?}, horizontal mirrors: {h:?}");
+            v.into_iter().sum::<usize>() + 100 * h.into_iter().sum::<usize>()
+        })
+        .sum()
+}
+
+/// A row or column of a pattern, one bit per `#`, over as many words as it takes.
+type Line = Vec<u64>;
 
-            // for each position (row, col) in a pattern
-            for row in 0..pat.len() {
-                for col in 0..pat.first().map_or(0, |line| line.len()) {
+/// A pattern with each row and column packed into bits.
+pub struct Pattern {
+    rows: Vec<Line>,
+    cols: Vec<Line>,
+}
 
-                    // create a copy of the original pattern with that position "de-smudged"
-                    let mut smudged = pat.clone();
-                    let mut newrow = smudged[row].clone().into_bytes();
-                    if let Some(ch) = newrow.get_mut(col) {
-                        *ch = match ch {
-                            b'#' => b'.',
-                            b'.' => b'#',
-                            _ => panic!("mutating invalid character"),
-                        }
-                    }
-                    smudged[row] = String::from_utf8(newrow).unwrap();
+impl From<&[&str]> for Pattern {
+    fn from(lines: &[&str]) -> Self {
+        let width = lines.first().map_or(0, |line| line.len());
 
-                    // find any new mirroring points (excluding ones we already knew about)
-                    v_mirrors(&smudged)
-                        .into_iter()
-                        .filter(|x| !init_v.contains(x))
-                        .for_each(|x| {
-                            debug!("smudge: {row}, {col} --> new vertical mirror found at {x}");
-                            new_v.insert(x);
-                        });
-                    h_mirrors(&smudged)
-                        .into_iter()
-                        .filter(|x| !init_h.contains(x))
-                        .for_each(|x| {
-                            debug!("smudge: {row}, {col} --> new horizontal mirror found at {x}");
-                            new_h.insert(x);
-                        });
+        let mut rows = vec![vec![0; width.div_ceil(64)]; lines.len()];
+        let mut cols = vec![vec![0; lines.len().div_ceil(64)]; width];
+        for (y, line) in lines.iter().enumerate() {
+            for (x, b) in line.bytes().enumerate() {
+                if b == b'#' {
+                    rows[y][x / 64] |= 1 << (x % 64);
+                    cols[x][y / 64] |= 1 << (y % 64);
                 }
             }
-            // return the calculation for any new mirror edges
-            new_v.into_iter().sum::<usize>() + (100 * new_h.into_iter().sum::<usize>())
-        })
+        }
+        Self { rows, cols }
+    }
+}
+
+/// A way a pattern can be symmetric. Axes between lines are given as the number of lines before
+/// them (like the puzzle's summaries), and axes through a line by that line's index.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Symmetry {
+    /// mirrored across a line between two columns
+    Vertical(usize),
+    /// mirrored across a line between two rows
+    Horizontal(usize),
+    /// mirrored across the middle of a column
+    ThroughColumn(usize),
+    /// mirrored across the middle of a row
+    ThroughRow(usize),
+    /// unchanged by a half turn of the whole pattern
+    Rotational,
+    /// mirrored across the top-left to bottom-right diagonal of the square starting at (`x`, `y`)
+    MainDiagonal { x: usize, y: usize, size: usize },
+    /// mirrored across the top-right to bottom-left diagonal of the square starting at (`x`, `y`)
+    AntiDiagonal { x: usize, y: usize, size: usize },
+}
+
+impl Pattern {
+    /// Reports every symmetry of the pattern which is at most `max_smudges` cells away from being
+    /// exact, along with exactly how many cells would need fixing. Like the puzzle, lines with no
+    /// partner on the other side of an axis are ignored. Diagonals are checked on every largest
+    /// square that fits in the pattern.
+    pub fn symmetries(&self, max_smudges: u32) -> Vec<(Symmetry, u32)> {
+        let (width, height) = (self.cols.len(), self.rows.len());
+        let mut found = vec![];
+
+        for axis in 1..width {
+            found.push((Symmetry::Vertical(axis), reflection_distance(&self.cols, axis, axis)));
+        }
+        for axis in 1..height {
+            found.push((Symmetry::Horizontal(axis), reflection_distance(&self.rows, axis, axis)));
+        }
+        for col in 1..width.saturating_sub(1) {
+            found.push((Symmetry::ThroughColumn(col), reflection_distance(&self.cols, col, col + 1)));
+        }
+        for row in 1..height.saturating_sub(1) {
+            found.push((Symmetry::ThroughRow(row), reflection_distance(&self.rows, row, row + 1)));
+        }
+
+        // every differing cell is counted twice (once from each end), but fixing either one will do
+        let rotated = self.rows.iter()
+            .zip(self.rows.iter().rev())
+            .map(|(a, b)| difference(a, &reverse(b, width)))
+            .sum::<u32>() / 2;
+        found.push((Symmetry::Rotational, rotated));
+
+        let size = width.min(height);
+        let squares = (0..=(width - size))
+            .flat_map(|x| (0..=(height - size)).map(move |y| (x, y)))
+            .filter(|_| size >= 2);
+        for (x, y) in squares {
+            // each row of the square against its matching column (read forwards or backwards)
+            let row = |i: usize| slice(&self.rows[y + i], x, size);
+            let col = |i: usize| slice(&self.cols[x + i], y, size);
+            let main = (0..size)
+                .map(|i| difference(&row(i), &col(i)))
+                .sum::<u32>() / 2;
+            let anti = (0..size)
+                .map(|i| difference(&row(i), &reverse(&col(size - 1 - i), size)))
+                .sum::<u32>() / 2;
+            found.push((Symmetry::MainDiagonal { x, y, size }, main));
+            found.push((Symmetry::AntiDiagonal { x, y, size }, anti));
+        }
+
+        found.retain(|&(_, smudges)| smudges <= max_smudges);
+        found
+    }
+}
+
+/// The number of differing bits between `lines` read backwards from `before` and forwards from
+/// `after`, stopping at whichever end comes first.
+fn reflection_distance(lines: &[Line], before: usize, after: usize) -> u32 {
+    lines[..before].iter()
+        .rev()
+        .zip(&lines[after..])
+        .map(|(a, b)| difference(a, b))
         .sum()
 }
 
-fn v_mirrors(pat: &[String]) -> Vec<usize> {
-    let width = pat.first().map(|line| line.len()).unwrap_or(0);
-    // for each column that could be a mirror point
-    (1..width).filter(|&col| {
-        // verify that on all lines
-        pat.iter().all(|line| {
-            // the left and right characters are mirrored
-            let (left, right) = line.split_at(col);
-            left.chars()
-                .rev()
-                .zip(right.chars())
-                .all(|(l, r)| l == r)
+/// the number of bits which differ between two lines of the same length
+fn difference(a: &[u64], b: &[u64]) -> u32 {
+    a.iter().zip(b).map(|(a, b)| (a ^ b).count_ones()).sum()
+}
+
+/// `len` bits of `line`, starting from bit `start`
+fn slice(line: &[u64], start: usize, len: usize) -> Line {
+    let (skip, shift) = (start / 64, start % 64);
+    let word = |i: usize| line.get(i).copied().unwrap_or(0);
+    let mut bits = (0..len.div_ceil(64))
+        .map(|i| match shift {
+            0 => word(skip + i),
+            _ => word(skip + i) >> shift | word(skip + i + 1) << (64 - shift),
         })
-    }).collect()
+        .collect::<Line>();
+    if let (Some(last), 1..) = (bits.last_mut(), len % 64) {
+        *last &= (1 << (len % 64)) - 1;
+    }
+    bits
+}
+
+/// reverses the lowest `len` bits of a line `len` bits long
+fn reverse(line: &[u64], len: usize) -> Line {
+    let reversed = line.iter().rev().map(|word| word.reverse_bits()).collect::<Line>();
+    slice(&reversed, reversed.len() * 64 - len, len)
+}
+
+/// Reports the symmetries of every pattern (see [`Pattern::symmetries`]).
+pub fn report_symmetries(input: &Input, max_smudges: u32) -> Vec<Vec<(Symmetry, u32)>> {
+    input.blocks()
+        .map(|pat| Pattern::from(&pat[..]).symmetries(max_smudges))
+        .collect()
 }
 
-fn h_mirrors(pat: &[String]) -> Vec<usize> {
-    let height = pat.len();
-    // for each row that could be a mirror point
-    (1..height).filter(|&row| {
-        // verify that lines above and below are mirrors
-        let (above, below) = pat.split_at(row);
-        above.iter()
-            .rev()
-            .zip(below.iter())
-            .all(|(a, b)| a == b)
-    }).collect()
+/// Finds every axis (as the number of lines before it) where the lines on either side reflect
+/// each other with exactly `smudges` differing bits between them.
+fn mirrors(lines: &[Line], smudges: u32) -> Vec<usize> {
+    (1..lines.len())
+        .filter(|&axis| reflection_distance(lines, axis, axis) == smudges)
+        .collect()
 }
 
+#[test]
+fn smudges_brute() {
+    log_init();
+    let input = get_input(get_day!(), "input");
+    for pat in input.blocks() {
+        let pat = &pat[..];
+        // count differences character-by-character instead
+        let diff = |a: &str, b: &str| a.chars().zip(b.chars()).filter(|(x, y)| x != y).count() as u32;
+        let transposed = (0..pat[0].len())
+            .map(|x| pat.iter().map(|line| &line[x..=x]).collect::<String>())
+            .collect::<Vec<_>>();
+        let transposed = transposed.iter().map(String::as_str).collect::<Vec<_>>();
+        let packed = Pattern::from(pat);
+        for smudges in 0..4 {
+            for (lines, bits) in [(pat, &packed.rows), (&transposed[..], &packed.cols)] {
+                let expected = (1..lines.len()).filter(|&axis| {
+                    let (before, after) = lines.split_at(axis);
+                    before.iter().rev().zip(after).map(|(a, b)| diff(a, b)).sum::<u32>() == smudges
+                }).collect::<Vec<_>>();
+                assert_eq!(mirrors(bits, smudges), expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn symmetries_square() {
+    log_init();
+    let square = ["#..", ".#.", "#.#"];
+    let report = Pattern::from(&square[..]).symmetries(1);
+    assert_eq!(report, [
+        (Symmetry::ThroughColumn(1), 1),
+        (Symmetry::ThroughRow(1), 1),
+        (Symmetry::Rotational, 1),
+        (Symmetry::MainDiagonal { x: 0, y: 0, size: 3 }, 1),
+        (Symmetry::AntiDiagonal { x: 0, y: 0, size: 3 }, 0),
+    ]);
+}
+
+#[test]
+fn large_pattern() {
+    log_init();
+    // a 100x70 pattern reflected across the middle row, then smudged once
+    let half = (0..35)
+        .map(|y| (0..100).map(|x| if (x * 7 + y * 3) % 5 < 2 { '#' } else { '.' }).collect::<String>())
+        .collect::<Vec<_>>();
+    let mut lines = half.iter().chain(half.iter().rev()).cloned().collect::<Vec<_>>();
+    let pat = Pattern::from(&lines.iter().map(String::as_str).collect::<Vec<_>>()[..]);
+    assert_eq!(mirrors(&pat.rows, 0), [35]);
+
+    let smudge = if lines[69].ends_with('#') { "." } else { "#" };
+    lines[69].replace_range(99..100, smudge);
+    let pat = Pattern::from(&lines.iter().map(String::as_str).collect::<Vec<_>>()[..]);
+    assert_eq!(mirrors(&pat.rows, 0), []);
+    assert_eq!(mirrors(&pat.rows, 1), [35]);
+    assert!(pat.symmetries(1).contains(&(Symmetry::Horizontal(35), 1)));
+
+    // bits pulled out of (and reversed across) word boundaries
+    let line = vec![1 << 63, 0b101];
+    assert_eq!(slice(&line, 63, 3), [0b011]);
+    assert_eq!(reverse(&slice(&line, 63, 4), 4), [0b1101]);
+    assert_eq!(reverse(&line, 128), [1 << 63 | 1 << 61, 1]);
+}
 
 testcase!(ex1, solve_part1, "example1", 5);
 testcase!(ex2, solve_part1, "example2", 400);
@@ -270,3 +416,15 @@ testcase!(part1, solve_part1, "input", 33122);
 testcase!(ex3, solve_part2, "example1", 300);
 testcase!(ex4, solve_part2, "example2", 100);
 testcase!(part2, solve_part2, "input", 32312);
+testcase!(smudged0, solve_smudged, "input", 33122, 0);
+testcase!(smudged1, solve_smudged, "input", 32312, 1);
+testcase!(smudged2, solve_smudged, "example2", 7, 2);
+testcase!(symmetries1, report_symmetries, "example1", vec![vec![
+    (Symmetry::Vertical(5), 0),
+    (Symmetry::Horizontal(3), 1),
+    (Symmetry::ThroughRow(5), 1),
+]], 1);
+testcase!(symmetries2, report_symmetries, "example2", vec![vec![
+    (Symmetry::Horizontal(1), 1),
+    (Symmetry::Horizontal(4), 0),
+]], 1);
@@ -19,7 +19,7 @@
 //! the platform, and the shape of the platform controls which ropes move and ultimately the focus
 //! of the dish.
 
-use std::{ops::{Index, IndexMut}, collections::HashMap};
+use std::{collections::HashMap, ops::Range};
 use super::*;
 type AdvInt = usize;
 
@@ -82,13 +82,12 @@ type AdvInt = usize;
 ///
 /// Tilt the platform so that the rounded rocks all roll north. Afterward, what is the total load
 /// on the north support beams?
-pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let input: Vec<String> = input.into_iter().collect();
-    let mut platform: Platform = input.as_slice().into();
+pub fn solve_part1(input: &Input) -> Result<AdvInt, String> {
+    let mut platform = Platform::from(&input.grid()?);
     debug!("Platform:\n{platform:?}");
-    platform.tilt();
+    platform.tilt(Direction::North);
     debug!("Platform (tilted):\n{platform:?}");
-    platform.weigh()
+    Ok(platform.weigh())
 }
 
 /// # Spin Cycles
@@ -154,154 +153,306 @@ pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
 ///
 /// Run the spin cycle for 1000000000 cycles. Afterward, what is the total load on the north
 /// support beams?
-pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
-    let input: Vec<String> = input.into_iter().collect();
-    let mut platform: Platform = input.as_slice().into();
-    let mut seen_platforms = HashMap::<Vec<u8>, u32>::new();
+pub fn solve_part2(input: &Input) -> Result<AdvInt, String> {
+    solve_program(input, "NWSE", 1_000_000_000, 'N')
+}
+
+/// Runs a program of tilts (like `NWSE` for a spin cycle) `repeats` times, then weighs the load on
+/// the support beams along the given `side`.
+pub fn solve_program(input: &Input, program: &str, repeats: usize, side: char) -> Result<AdvInt, String> {
+    let mut platform = Platform::from(&input.grid()?);
+    let program = program.chars()
+        .map(Direction::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
     debug!("Platform:\n{platform:?}");
-    seen_platforms.insert(platform.map.clone(), 0);
-
-    const CYCLES: u32 = 1_000_000_000;
-    for iteration in 1..=CYCLES {
-        platform.spin_cycle();
-        debug!("Platform (cycle: {iteration}):\n{platform:?}\nWeight: {}", platform.weigh());
-        // check if we hit a loop and "fast-forward"
-        if let Some(prev_iteration) = seen_platforms.insert(platform.map.clone(), iteration) {
-            let repeating = iteration - prev_iteration;
-            let remaining = (CYCLES - iteration) % repeating;
-            info!("Same platform on cycle {iteration} as on prior cycle {prev_iteration}.");
-            info!("Fast-forwarding {} cycles, then cycling {remaining} more times...", CYCLES - iteration);
-            for _ in 1..=remaining {
-                platform.spin_cycle();
-            }
-            break;
+    platform.run(&program, repeats);
+    Ok(platform.load(side.try_into()?))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+    North,
+    West,
+    South,
+    East,
+}
+
+impl TryFrom<char> for Direction {
+    type Error = String;
+    fn try_from(ch: char) -> Result<Self, Self::Error> {
+        match ch {
+            'N' => Ok(Direction::North),
+            'W' => Ok(Direction::West),
+            'S' => Ok(Direction::South),
+            'E' => Ok(Direction::East),
+            _ => Err(format!("invalid direction: {ch}")),
+        }
+    }
+}
+
+/// A row (or column) of cells, one bit per cell, over as many words as it takes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Bits(Vec<u64>);
+
+impl Bits {
+    fn new(len: usize) -> Self {
+        Bits(vec![0; len.div_ceil(64)])
+    }
+
+    fn get(&self, i: usize) -> bool {
+        self.0[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    fn set(&mut self, i: usize) {
+        self.0[i / 64] |= 1 << (i % 64);
+    }
+
+    /// Each word covering part of `range`, along with a mask of the bits it covers.
+    fn words(range: Range<usize>) -> impl Iterator<Item = (usize, u64)> {
+        (range.start / 64..range.end.div_ceil(64)).map(move |w| {
+            let lo = range.start.max(w * 64) - w * 64;
+            let hi = range.end.min(w * 64 + 64) - w * 64;
+            let mask = match hi - lo {
+                64 => u64::MAX,
+                n => ((1 << n) - 1) << lo,
+            };
+            (w, mask)
+        })
+    }
+
+    /// How many bits in `range` are set.
+    fn count(&self, range: Range<usize>) -> usize {
+        Self::words(range).map(|(w, mask)| (self.0[w] & mask).count_ones() as usize).sum()
+    }
+
+    fn set_range(&mut self, range: Range<usize>) {
+        for (w, mask) in Self::words(range) {
+            self.0[w] |= mask;
         }
     }
 
-    platform.weigh()
+    /// The index of every set bit, lowest first.
+    fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(w, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                let bit = word.trailing_zeros() as usize;
+                word &= word.wrapping_sub(1);
+                (bit < 64).then_some(w * 64 + bit)
+            })
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Axis {
+    Rows,
+    Cols,
 }
 
+/// The rocks on the platform, as a bitset for each row and for each column.
+///
+/// Tilting rolls the round rocks along one axis only, so just that axis's sets are kept up to date
+/// as the rocks roll; the other axis is rebuilt from it the next time it's needed.
 #[derive(Clone)]
-struct Platform {
-    map: Vec<u8>,
+pub struct Platform {
+    round_rows: Vec<Bits>,
+    round_cols: Vec<Bits>,
+    /// which of `round_rows` and `round_cols` has the rocks where they are now
+    current: Axis,
+    cube_rows: Vec<Bits>,
+    /// the stretches of each row between cube rocks
+    row_spans: Vec<Vec<Range<usize>>>,
+    /// the stretches of each column between cube rocks
+    col_spans: Vec<Vec<Range<usize>>>,
     width: usize,
     height: usize,
 }
 
-impl From<&[String]> for Platform {
-    fn from(lines: &[String]) -> Self {
-        let mut width = 0;
-        let mut height = 0;
-        let map: Vec<u8> = lines.iter()
-            .flat_map(|line| {
-                height += 1;
-                width = width.max(line.len());
-                line.as_bytes().to_owned()
-            })
-            .collect();
+impl From<&Grid<'_>> for Platform {
+    fn from(grid: &Grid) -> Self {
+        let (width, height) = (grid.width(), grid.height());
+        let sets = |rock: u8| {
+            let (mut rows, mut cols) = (vec![Bits::new(width); height], vec![Bits::new(height); width]);
+            for (y, line) in grid.rows().enumerate() {
+                for x in (0..width).filter(|&x| line[x] == rock) {
+                    rows[y].set(x);
+                    cols[x].set(y);
+                }
+            }
+            (rows, cols)
+        };
+        let (round_rows, round_cols) = sets(b'O');
+        // cube rocks never move, so their columns are only needed to find the spans
+        let (cube_rows, cube_cols) = sets(b'#');
 
-        // verify flattening assumptions
-        debug_assert_eq!(width * height, map.len());
+        let spans = |cubes: &Bits, len: usize| {
+            let mut spans = vec![];
+            let mut start = 0;
+            for end in cubes.ones().chain([len]) {
+                if end > start {
+                    spans.push(start..end);
+                }
+                start = end + 1;
+            }
+            spans
+        };
+        let row_spans = cube_rows.iter().map(|row| spans(row, width)).collect();
+        let col_spans = cube_cols.iter().map(|col| spans(col, height)).collect();
 
         Self {
-            map,
+            round_rows,
+            round_cols,
+            current: Axis::Rows,
+            cube_rows,
+            row_spans,
+            col_spans,
             width,
-            height
+            height,
         }
     }
 }
 
 impl Platform {
-    fn tilt(&mut self) {
-        // for each column
-        for x in 0..self.width {
-            // for each row in that column, try to move each found stone up (until they cant)
-            for y in 0..self.height {
-                if self[(x, y)] == b'O' {
-                    // found a stone? move it up to highest available empty space
-                    let mut newrow = y;
-                    for row in (0..y).rev() {
-                        match self[(x, row)] {
-                           b'.' => newrow = row,
-                           _ => break,
-                        }
-                    }
-                    if newrow != y {
-                        // swap and resume stone search
-                        self[(x, newrow)] = b'O';
-                        self[(x, y)] = b'.';
-                        //debug!("moved stone at {x}, {y} to {x}, {newrow}");
-                    } else {
-                        // note that we can't move the stone at all (found an obstacle)
-                        //debug!("couldn't move stone at {x}, {y}");
-                    }
+    /// Rolls every round rock as far as it will go towards `dir`.
+    pub fn tilt(&mut self, dir: Direction) {
+        match dir {
+            Direction::North => self.pack(Axis::Cols, false),
+            Direction::South => self.pack(Axis::Cols, true),
+            Direction::West => self.pack(Axis::Rows, false),
+            Direction::East => self.pack(Axis::Rows, true),
+        }
+    }
+
+    /// Packs the stones in each span between cube rocks (along each row or column) to its low
+    /// (north/west) or high (south/east) end.
+    fn pack(&mut self, axis: Axis, high: bool) {
+        self.sync(axis);
+        let (lines, spans, len) = match axis {
+            Axis::Rows => (&mut self.round_rows, &self.row_spans, self.width),
+            Axis::Cols => (&mut self.round_cols, &self.col_spans, self.height),
+        };
+        for (line, spans) in lines.iter_mut().zip(spans) {
+            let mut packed = Bits::new(len);
+            for span in spans {
+                let stones = line.count(span.clone());
+                match high {
+                    true => packed.set_range(span.end - stones..span.end),
+                    false => packed.set_range(span.start..span.start + stones),
                 }
             }
+            *line = packed;
         }
     }
 
-    /// rotates the map clockwise by 90 degrees
-    fn rotate(&mut self) {
-        let old = self.clone();
-        for j in 0..old.height {
-            for i in 0..old.width {
-                self[(old.height - 1 - j, i)] = old[(i, j)];
+    /// Brings the round rocks along `axis` up to date, if they aren't already.
+    fn sync(&mut self, axis: Axis) {
+        if self.current == axis {
+            return;
+        }
+        let (from, to, len) = match axis {
+            Axis::Rows => (&self.round_cols, &mut self.round_rows, self.width),
+            Axis::Cols => (&self.round_rows, &mut self.round_cols, self.height),
+        };
+        to.iter_mut().for_each(|line| *line = Bits::new(len));
+        for (i, line) in from.iter().enumerate() {
+            for j in line.ones() {
+                to[j].set(i);
             }
         }
+        self.current = axis;
+    }
 
-        (self.width, self.height) = (self.height, self.width);
+    /// Whether there's a round rock at (`x`, `y`).
+    fn is_round(&self, x: usize, y: usize) -> bool {
+        match self.current {
+            Axis::Rows => self.round_rows[y].get(x),
+            Axis::Cols => self.round_cols[x].get(y),
+        }
     }
 
-    /// (tilt then rotate) x4
-    fn spin_cycle(&mut self) {
-        for _ in 1..=4 {
-            self.tilt();
-            self.rotate();
+    /// The (`x`, `y`) of every round rock.
+    fn stones(&self) -> Vec<(usize, usize)> {
+        match self.current {
+            Axis::Rows => self.round_rows.iter()
+                .enumerate()
+                .flat_map(|(y, row)| row.ones().map(move |x| (x, y)))
+                .collect(),
+            Axis::Cols => self.round_cols.iter()
+                .enumerate()
+                .flat_map(|(x, col)| col.ones().map(move |y| (x, y)))
+                .collect(),
         }
     }
 
-    fn weigh(&mut self) -> usize {
-        // on each level (top to bottom)
-        (0..self.height).map(|row| {
-            // count the number of round stones
-            let found = self.map[(self.width*row)..(self.width*row + self.width)]
-                .iter()
-                .filter(|&&c| c == b'O')
-                .count();
-            // compute (and return) the "weighted" count
-            let level = self.height - row;
-            //debug!("on level: {level} found {found} stones");
-            found * level
-        }).sum()
+    /// The round rocks, packed into as few words as possible, to tell layouts apart.
+    fn key(&mut self) -> Vec<u64> {
+        self.sync(Axis::Rows);
+        self.round_rows.iter().flat_map(|row| row.0.iter().copied()).collect()
     }
-}
 
-impl Index<(usize, usize)> for Platform {
-    type Output = u8;
-    fn index(&self, index: (usize, usize)) -> &Self::Output {
-        if index.0 >= self.width || index.1 >= self.height {
-            panic!("invalid index into platform map");
-        } else {
-            &self.map[index.0 + index.1 * self.width]
+    /// tilt north, west, south, then east
+    pub fn spin_cycle(&mut self) {
+        for dir in [Direction::North, Direction::West, Direction::South, Direction::East] {
+            self.tilt(dir);
         }
     }
-}
 
-impl IndexMut<(usize, usize)> for Platform {
-    fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
-        if index.0 >= self.width || index.1 >= self.height {
-            panic!("invalid index into platform map");
-        } else {
-            &mut self.map[index.0 + index.1 * self.width]
+    /// Tilts in each direction of `program` in turn, `repeats` times over. Once the platform
+    /// repeats a layout from the end of an earlier run, it fast-forwards through the loop.
+    pub fn run(&mut self, program: &[Direction], repeats: usize) {
+        let mut seen_platforms = HashMap::<Vec<u64>, usize>::new();
+        seen_platforms.insert(self.key(), 0);
+
+        for iteration in 1..=repeats {
+            for &dir in program {
+                self.tilt(dir);
+            }
+            debug!("Platform (run: {iteration}):\n{self:?}\nWeight: {}", self.weigh());
+            // check if we hit a loop and "fast-forward"
+            if let Some(prev_iteration) = seen_platforms.insert(self.key(), iteration) {
+                let repeating = iteration - prev_iteration;
+                let remaining = (repeats - iteration) % repeating;
+                info!("Same platform on run {iteration} as on prior run {prev_iteration}.");
+                info!("Fast-forwarding {} runs, then running {remaining} more times...", repeats - iteration);
+                for _ in 1..=remaining {
+                    for &dir in program {
+                        self.tilt(dir);
+                    }
+                }
+                break;
+            }
+        }
+    }
+
+    /// The load on the north support beams.
+    pub fn weigh(&self) -> usize {
+        self.load(Direction::North)
+    }
+
+    /// The load on the support beams along the given side: each round rock counts for how many
+    /// rows (or columns) away from the opposite edge it is.
+    pub fn load(&self, side: Direction) -> usize {
+        let stones = self.stones().into_iter();
+        match side {
+            Direction::North => stones.map(|(_, y)| self.height - y).sum(),
+            Direction::South => stones.map(|(_, y)| y + 1).sum(),
+            Direction::West => stones.map(|(x, _)| self.width - x).sum(),
+            Direction::East => stones.map(|(x, _)| x + 1).sum(),
         }
     }
 }
 
 impl std::fmt::Debug for Platform {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for j in 0..self.height {
-            for i in 0..self.width {
-                write!(f, "{}", self.map[i + self.width * j] as char)?
+        for (y, cubes) in self.cube_rows.iter().enumerate() {
+            for x in 0..self.width {
+                let ch = match (self.is_round(x, y), cubes.get(x)) {
+                    (true, _) => 'O',
+                    (_, true) => '#',
+                    _ => '.',
+                };
+                write!(f, "{ch}")?
             }
             writeln!(f)?
         }
@@ -309,7 +460,66 @@ impl std::fmt::Debug for Platform {
     }
 }
 
-testcase!(ex1, solve_part1, "example", 136);
-testcase!(part1, solve_part1, "input", 109665);
-testcase!(ex2, solve_part2, "example", 64);
-testcase!(part2, solve_part2, "input", 96061);
+#[test]
+fn cycle_ex() -> Result<(), String> {
+    log_init();
+    let mut platform = Platform::from(&get_input(get_day!(), "example").grid()?);
+    platform.spin_cycle();
+    assert_eq!(format!("{platform:?}"), "\
+.....#....
+....#...O#
+...OO##...
+.OO#......
+.....OOO#.
+.O#...O#.#
+....O#....
+......OOOO
+#...O###..
+#..OO#....
+");
+    Ok(())
+}
+
+#[test]
+fn load_sides() -> Result<(), String> {
+    log_init();
+    let mut platform = Platform::from(&get_input(get_day!(), "example").grid()?);
+    platform.tilt(Direction::West);
+    // each of the 18 rocks weighs (width + 1) across both opposite sides
+    assert_eq!(platform.load(Direction::West) + platform.load(Direction::East), 18 * 11);
+    assert_eq!(platform.load(Direction::North) + platform.load(Direction::South), 18 * 11);
+    assert_eq!(platform.load(Direction::West), 147);
+    assert!(solve_program(&Input::from("O.\n#"), "N", 1, 'N').is_err());
+    Ok(())
+}
+
+#[test]
+fn wide_platform() -> Result<(), String> {
+    log_init();
+    // wider (and taller) than a machine word, with rocks on either side of the word boundaries
+    let mut lines = vec![".".repeat(200); 70];
+    lines[69].replace_range(150..151, "O");
+    lines[69].replace_range(63..65, "O#");
+    lines[10].replace_range(150..151, "#");
+    let input = Input::from(lines.join("\n"));
+    let mut platform = Platform::from(&input.grid()?);
+
+    platform.tilt(Direction::North);
+    assert_eq!(platform.stones(), [(63, 0), (150, 11)]);
+    platform.tilt(Direction::East);
+    assert_eq!(platform.stones(), [(199, 0), (199, 11)]);
+    platform.tilt(Direction::South);
+    assert_eq!(platform.load(Direction::South), 70 + 69);
+    platform.tilt(Direction::West);
+    assert_eq!(platform.stones(), [(0, 68), (65, 69)]);
+    assert_eq!(platform.load(Direction::West), 200 + 135);
+    Ok(())
+}
+
+testcase!(ex1, solve_part1, "example", Ok(136));
+testcase!(part1, solve_part1, "input", Ok(109665));
+testcase!(ex2, solve_part2, "example", Ok(64));
+testcase!(part2, solve_part2, "input", Ok(96061));
+testcase!(program_tilt, solve_program, "example", Ok(136), "N", 1, 'N');
+testcase!(program_cycles, solve_program, "example", Ok(69), "NWSE", 3, 'N');
+testcase!(program_spin, solve_program, "input", Ok(96061), "NWSENWSE", 500_000_000, 'N');
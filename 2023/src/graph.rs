@@ -0,0 +1,446 @@
+//! Searches over graphs which are never built up front: each takes some starting node(s) and a
+//! function listing the neighbours of any node, so nodes can be whatever's convenient (grid
+//! coordinates, names, whole puzzle states).
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    fmt::Debug,
+    hash::Hash,
+    ops::Add,
+};
+
+/// Something a graph can be searched over.
+pub trait Node: Clone + Eq + Hash {}
+impl<N: Clone + Eq + Hash> Node for N {}
+
+/// The weight of an edge (or a whole path), where [`Default`] is a weight of nothing.
+pub trait Cost: Copy + Ord + Default + Add<Output = Self> {}
+impl<C: Copy + Ord + Default + Add<Output = C>> Cost for C {}
+
+/// The shortest distance to every node a search reached, along with the node before it on one
+/// of those shortest paths.
+#[derive(Clone, Debug)]
+pub struct Paths<N, C> {
+    distances: HashMap<N, C>,
+    previous: HashMap<N, N>,
+    /// every node reached, nearest first
+    order: Vec<N>,
+}
+
+/// Nothing reached at all.
+impl<N, C> Default for Paths<N, C> {
+    fn default() -> Self {
+        Self { distances: HashMap::new(), previous: HashMap::new(), order: vec![] }
+    }
+}
+
+impl<N: Node, C: Cost> Paths<N, C> {
+    /// The shortest distance to `node` from any start, if it was reached.
+    pub fn distance(&self, node: &N) -> Option<C> {
+        self.distances.get(node).copied()
+    }
+
+    pub fn contains(&self, node: &N) -> bool {
+        self.distances.contains_key(node)
+    }
+
+    /// Every node reached, nearest first (ties in whichever order they were found).
+    pub fn reached(&self) -> &[N] {
+        &self.order
+    }
+
+    /// The node farthest from the starts, and its distance.
+    pub fn farthest(&self) -> Option<(&N, C)> {
+        let node = self.order.last()?;
+        Some((node, self.distances[node]))
+    }
+
+    /// A shortest path from one of the starts to `node` (including both ends).
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        self.contains(node).then(|| walk_back(&self.previous, node.clone()))
+    }
+}
+
+/// Follows `previous` back from `node`, returning the path up to it.
+fn walk_back<N: Node>(previous: &HashMap<N, N>, node: N) -> Vec<N> {
+    let mut path = vec![node];
+    while let Some(prev) = previous.get(path.last().unwrap()) {
+        path.push(prev.clone());
+    }
+    path.reverse();
+    path
+}
+
+/// Breadth-first search from every node in `starts` at once, where every edge counts as 1.
+pub fn bfs<N, S, F, I>(starts: S, mut neighbours: F) -> Paths<N, usize>
+where
+    N: Node,
+    S: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    let mut paths = Paths::default();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if paths.distances.insert(start.clone(), 0).is_none() {
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        let dist = paths.distances[&node];
+        for next in neighbours(&node) {
+            if !paths.distances.contains_key(&next) {
+                paths.distances.insert(next.clone(), dist + 1);
+                paths.previous.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+        paths.order.push(node);
+    }
+
+    paths
+}
+
+/// Dijkstra's algorithm from every node in `starts` at once, where `neighbours` lists each
+/// neighbour along with the cost of getting there. Costs can't be negative.
+pub fn dijkstra<N, C, S, F, I>(starts: S, mut neighbours: F) -> Paths<N, C>
+where
+    N: Node,
+    C: Cost,
+    S: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut paths = Paths::default();
+    let mut best = HashMap::new();
+    let mut queue = Queue::default();
+    for start in starts {
+        best.insert(start.clone(), C::default());
+        queue.push(C::default(), start);
+    }
+
+    while let Some((dist, node)) = queue.pop() {
+        if paths.distances.contains_key(&node) || best[&node] < dist {
+            continue;
+        }
+        for (next, cost) in neighbours(&node) {
+            let through = dist + cost;
+            if !paths.distances.contains_key(&next) && best.get(&next).is_none_or(|&d| through < d) {
+                best.insert(next.clone(), through);
+                paths.previous.insert(next.clone(), node.clone());
+                queue.push(through, next);
+            }
+        }
+        paths.distances.insert(node.clone(), dist);
+        paths.order.push(node);
+    }
+
+    paths
+}
+
+/// A* search from `start` to the nearest node where `is_goal` holds, returning its distance and
+/// the path there.
+///
+/// `heuristic` guesses the distance left from a node to a goal. So long as it never guesses too
+/// high, the path found is a shortest one. Nodes are searched again whenever a shorter way to them
+/// turns up, so the heuristic doesn't also need to be consistent, although a consistent one never
+/// searches a node twice.
+pub fn astar<N, C, F, I, G, H>(start: N, mut neighbours: F, mut is_goal: G, mut heuristic: H) -> Option<(C, Vec<N>)>
+where
+    N: Node,
+    C: Cost,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+    G: FnMut(&N) -> bool,
+    H: FnMut(&N) -> C,
+{
+    let mut best = HashMap::from([(start.clone(), C::default())]);
+    let mut previous = HashMap::new();
+    let mut queue = Queue::default();
+    queue.push(heuristic(&start), (start, C::default()));
+
+    while let Some((_, (node, dist))) = queue.pop() {
+        // skip anything queued before a shorter way to it was found
+        if best[&node] < dist {
+            continue;
+        }
+        if is_goal(&node) {
+            return Some((dist, walk_back(&previous, node)));
+        }
+        for (next, cost) in neighbours(&node) {
+            let through = dist + cost;
+            if best.get(&next).is_none_or(|&d| through < d) {
+                best.insert(next.clone(), through);
+                previous.insert(next.clone(), node.clone());
+                queue.push(through + heuristic(&next), (next, through));
+            }
+        }
+    }
+
+    None
+}
+
+/// A min-priority queue which doesn't need its items to be ordered (ties come out first-in,
+/// first-out).
+struct Queue<N, C> {
+    heap: BinaryHeap<Reverse<(C, usize)>>,
+    items: HashMap<usize, N>,
+    pushed: usize,
+}
+
+impl<N, C: Ord> Default for Queue<N, C> {
+    fn default() -> Self {
+        Self { heap: BinaryHeap::new(), items: HashMap::new(), pushed: 0 }
+    }
+}
+
+impl<N, C: Copy + Ord> Queue<N, C> {
+    fn push(&mut self, priority: C, item: N) {
+        self.heap.push(Reverse((priority, self.pushed)));
+        self.items.insert(self.pushed, item);
+        self.pushed += 1;
+    }
+
+    fn pop(&mut self) -> Option<(C, N)> {
+        let Reverse((priority, i)) = self.heap.pop()?;
+        Some((priority, self.items.remove(&i).unwrap()))
+    }
+}
+
+/// Orders `nodes` (and anything reachable from them) so that every node comes before all of its
+/// neighbours. Fails if there's a cycle.
+pub fn toposort<N, S, F, I>(nodes: S, mut neighbours: F) -> Result<Vec<N>, String>
+where
+    N: Node + Debug,
+    S: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    // depth-first, where a node is `false` while it's being visited and `true` once it's done
+    let mut state = HashMap::<N, bool>::new();
+    let mut sorted = vec![];
+    for root in nodes {
+        if state.contains_key(&root) {
+            continue;
+        }
+        state.insert(root.clone(), false);
+        let mut stack = vec![(root.clone(), neighbours(&root).into_iter().collect::<Vec<_>>())];
+        while let Some((node, next)) = stack.last_mut() {
+            let Some(next) = next.pop() else {
+                state.insert(node.clone(), true);
+                sorted.push(stack.pop().unwrap().0);
+                continue;
+            };
+            match state.get(&next) {
+                Some(true) => {}
+                Some(false) => return Err(format!("cycle through {next:?}")),
+                None => {
+                    state.insert(next.clone(), false);
+                    let after = neighbours(&next).into_iter().collect();
+                    stack.push((next, after));
+                }
+            }
+        }
+    }
+
+    sorted.reverse();
+    Ok(sorted)
+}
+
+/// Disjoint sets of the numbers `0..len`, which can be merged together.
+#[derive(Clone, Debug)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    /// Every number starts out in a set of its own.
+    pub fn new(len: usize) -> Self {
+        Self { parent: (0..len).collect(), size: vec![1; len] }
+    }
+
+    /// The number standing in for the whole set holding `x`.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        // point everything on the way straight at the root
+        let mut x = x;
+        while self.parent[x] != root {
+            x = std::mem::replace(&mut self.parent[x], root);
+        }
+        root
+    }
+
+    /// Merges the sets holding `a` and `b`, returning whether they were separate.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        let (big, small) = if self.size[a] >= self.size[b] { (a, b) } else { (b, a) };
+        self.parent[small] = big;
+        self.size[big] += self.size[small];
+        true
+    }
+
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// How many numbers are in the set holding `x`.
+    pub fn size_of(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    /// Every set, each in increasing order, ordered by their smallest numbers.
+    pub fn sets(&mut self) -> Vec<Vec<usize>> {
+        let mut sets = Vec::<Vec<usize>>::new();
+        let mut index = HashMap::new();
+        for x in 0..self.parent.len() {
+            let root = self.find(x);
+            let i = *index.entry(root).or_insert_with(|| {
+                sets.push(vec![]);
+                sets.len() - 1
+            });
+            sets[i].push(x);
+        }
+        sets
+    }
+}
+
+/// Splits `nodes` (and anything reachable from them) into connected components, in the order of
+/// the first node found of each, with each component in breadth-first order.
+///
+/// Edges are only followed the way `neighbours` lists them, so for a directed graph this finds
+/// what's reachable from each node not already in a component.
+pub fn components<N, S, F, I>(nodes: S, mut neighbours: F) -> Vec<Vec<N>>
+where
+    N: Node,
+    S: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    let mut seen = HashSet::new();
+    let mut components = vec![];
+    for node in nodes {
+        if seen.contains(&node) {
+            continue;
+        }
+        let component = bfs([node], |n| {
+            neighbours(n).into_iter().filter(|next| !seen.contains(next)).collect::<Vec<_>>()
+        }).order;
+        seen.extend(component.iter().cloned());
+        components.push(component);
+    }
+    components
+}
+
+#[cfg(test)]
+fn grid_neighbours<'a>(walls: &'a [&'a str]) -> impl Fn(&(usize, usize)) -> Vec<(usize, usize)> + 'a {
+    move |&(x, y)| {
+        [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)].into_iter()
+            .filter(|&(x, y)| walls.get(y).and_then(|row| row.as_bytes().get(x)) == Some(&b'.'))
+            .collect()
+    }
+}
+
+#[test]
+fn unweighted() {
+    let maze = ["..#....", ".##.##.", "...#...", ".#...#."];
+    let paths = bfs([(0, 0)], grid_neighbours(&maze));
+    assert_eq!(paths.distance(&(6, 0)), Some(12));
+    assert_eq!(paths.distance(&(2, 0)), None);
+    assert_eq!(paths.farthest(), Some((&(3, 1), 16)));
+    let path = paths.path_to(&(4, 3)).unwrap();
+    assert_eq!(path, [(0, 0), (0, 1), (0, 2), (1, 2), (2, 2), (2, 3), (3, 3), (4, 3)]);
+
+    // from several starts at once
+    let paths = bfs([(0, 0), (6, 0)], grid_neighbours(&maze));
+    assert_eq!(paths.distance(&(3, 3)), Some(6));
+    assert_eq!(paths.path_to(&(5, 0)).unwrap(), [(6, 0), (5, 0)]);
+}
+
+#[test]
+fn weighted() {
+    // a road network, with the distances between towns
+    let roads = [("a", "b", 7), ("a", "c", 9), ("a", "f", 14), ("b", "c", 10), ("b", "d", 15),
+        ("c", "d", 11), ("c", "f", 2), ("d", "e", 6), ("e", "f", 9)];
+    let neighbours = |town: &&str| roads.iter()
+        .filter_map(|&(a, b, miles)| match *town {
+            t if t == a => Some((b, miles)),
+            t if t == b => Some((a, miles)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let paths = dijkstra(["a"], neighbours);
+    assert_eq!(paths.distance(&"e"), Some(20));
+    assert_eq!(paths.path_to(&"e").unwrap(), ["a", "c", "f", "e"]);
+    assert_eq!(paths.reached(), ["a", "b", "c", "f", "d", "e"]);
+    assert_eq!(paths.distance(&"z"), None);
+
+    // without a heuristic A* is just Dijkstra, and a good one gives the same answer
+    assert_eq!(astar("a", neighbours, |&t| t == "e", |_| 0), Some((20, vec!["a", "c", "f", "e"])));
+    let guess = |t: &&str| match *t { "a" => 15, "b" => 18, "c" => 9, "d" => 6, "f" => 9, _ => 0 };
+    assert_eq!(astar("a", neighbours, |&t| t == "e", guess).map(|(d, _)| d), Some(20));
+    assert_eq!(astar("a", neighbours, |&t| t == "z", |_| 0), None);
+
+    // a heuristic which never guesses too high, but isn't consistent: `c` is first reached the
+    // long way, and has to be searched again once the way through `a` turns up
+    let edges = HashMap::from([("s", vec![("a", 1), ("c", 3)]), ("a", vec![("c", 1)]), ("c", vec![("g", 3)]),
+        ("g", vec![])]);
+    let inconsistent = |n: &&str| if *n == "a" { 4 } else { 0 };
+    let found = astar("s", |n| edges[n].clone(), |&n| n == "g", inconsistent);
+    assert_eq!(found, Some((5, vec!["s", "a", "c", "g"])));
+
+    // BFS on a grid, against A* with the Manhattan distance
+    let maze = ["..#....", ".##.##.", "...#...", ".#...#."];
+    let steps = |n: &(usize, usize)| grid_neighbours(&maze)(n).into_iter().map(|m| (m, 1));
+    let manhattan = |&(x, y): &(usize, usize)| x.abs_diff(6) + y.abs_diff(0);
+    let (dist, path) = astar((0, 0), steps, |&n| n == (6, 0), manhattan).unwrap();
+    assert_eq!(Some(dist), bfs([(0, 0)], grid_neighbours(&maze)).distance(&(6, 0)));
+    assert_eq!(path.len(), dist + 1);
+}
+
+#[test]
+fn ordering() {
+    // each step has to happen before the ones it leads to
+    let steps = HashMap::from([("C", vec!["A", "F"]), ("A", vec!["B", "D"]), ("B", vec!["E"]),
+        ("D", vec!["E"]), ("F", vec!["E"]), ("E", vec![])]);
+    let sorted = toposort(["C"], |s| steps[s].clone()).unwrap();
+    assert_eq!(sorted.len(), 6);
+    for (step, after) in &steps {
+        let pos = |s| sorted.iter().position(|t| t == s).unwrap();
+        assert!(after.iter().all(|a| pos(step) < pos(a)), "{step} is out of order in {sorted:?}");
+    }
+
+    let looped = HashMap::from([(1, vec![2]), (2, vec![3]), (3, vec![1]), (0, vec![1])]);
+    assert!(toposort([0], |n| looped[n].clone()).is_err());
+}
+
+#[test]
+fn disjoint_sets() {
+    let mut sets = UnionFind::new(6);
+    assert!(sets.union(0, 3));
+    assert!(sets.union(4, 3));
+    assert!(!sets.union(0, 4));
+    assert!(sets.union(1, 5));
+    assert!(sets.same(0, 4));
+    assert!(!sets.same(0, 1));
+    assert_eq!(sets.size_of(3), 3);
+    assert_eq!(sets.sets(), [vec![0, 3, 4], vec![1, 5], vec![2]]);
+
+    // the same rooms found by searching the grid
+    let rooms = ["..#..", "..#..", "#####", "....."];
+    let open = (0..4).flat_map(|y| (0..5).map(move |x| (x, y)))
+        .filter(|&(x, y)| rooms[y].as_bytes()[x] == b'.');
+    let parts = components(open, grid_neighbours(&rooms));
+    assert_eq!(parts.iter().map(Vec::len).collect::<Vec<_>>(), [4, 4, 5]);
+    assert_eq!(parts[1][0], (3, 0));
+}
@@ -14,13 +14,14 @@ pub mod day11;
 pub mod day12;
 pub mod day13;
 pub mod day14;
+pub mod graph;
+pub mod parse;
 
 use log::*;
 
 use std::{
-    fmt::Display,
     fs::File,
-    io::{BufRead, BufReader},
+    io::Read,
     path::Path
 };
 
@@ -29,24 +30,124 @@ pub fn log_init() {
     let _ = env_logger::builder().format_timestamp(None).try_init();
 }
 
-/// Takes a path to a file and returns a [BufReader](std::io::BufReader) over its contents.
-fn get_reader<P: AsRef<Path> + Copy + Display>(path: P) -> std::io::Result<BufReader<File>> {
-    let input = std::fs::File::open(path.as_ref())?;
-    Ok(BufReader::new(input))
-}
-
 fn get_filename<S: AsRef<str>>(day: u8, file: S) -> String {
     format!("inputs/day{day:02}/{}", file.as_ref())
 }
 
-/// Loads a test file into a `Vec<String>` (one String per line), given a day and filename
-pub fn get_input<S: AsRef<str>>(day: u8, kind: S) -> Vec<String> {
+/// Loads a test file into an [`Input`], given a day and filename
+pub fn get_input<S: AsRef<str>>(day: u8, kind: S) -> Input {
     let filename = get_filename(day, kind.as_ref());
-    get_reader(filename.as_str())
-        .unwrap_or_else(|_| panic!("failed to open test input file: day{day:02} - {}", kind.as_ref()))
-        .lines()
-        .map(|l| l.expect("i/o error when reading"))
-        .collect()
+    Input::open(filename.as_str())
+        .unwrap_or_else(|e| panic!("failed to open test input file: day{day:02} - {}: {e}", kind.as_ref()))
+}
+
+/// A puzzle input, held as one contiguous (UTF-8) buffer.
+///
+/// Everything handed out (lines, blocks of lines, grids) borrows from that buffer, so nothing is
+/// copied after the input is loaded.
+pub struct Input {
+    text: String,
+}
+
+impl Input {
+    /// Reads the whole file at `path`, which has to be UTF-8.
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Reads everything from `reader` (such as stdin) into one buffer.
+    pub fn from_reader<R: Read>(mut reader: R) -> std::io::Result<Self> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        Ok(Self { text })
+    }
+
+    /// The whole input as text.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.as_str().as_bytes()
+    }
+
+    /// Each line of the input, without its line ending.
+    pub fn lines(&self) -> std::str::Lines<'_> {
+        self.as_str().lines()
+    }
+
+    /// Each line of the input as bytes, without its line ending.
+    pub fn byte_lines(&self) -> impl Iterator<Item = &[u8]> {
+        self.lines().map(str::as_bytes)
+    }
+
+    /// Each block of lines separated by blank lines (leaving out any empty blocks).
+    pub fn blocks(&self) -> impl Iterator<Item = Vec<&str>> {
+        parse::sections(self.as_str())
+    }
+
+    /// The input as a rectangular grid of bytes.
+    pub fn grid(&self) -> Result<Grid<'_>, String> {
+        Grid::new(self.byte_lines())
+    }
+}
+
+impl From<&str> for Input {
+    fn from(text: &str) -> Self {
+        Self { text: text.to_string() }
+    }
+}
+
+impl From<String> for Input {
+    fn from(text: String) -> Self {
+        Self { text }
+    }
+}
+
+impl std::fmt::Debug for Input {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Input({} bytes)", self.text.len())
+    }
+}
+
+/// Rows of bytes which all have the same width, borrowed from an [`Input`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<'a> {
+    rows: Vec<&'a [u8]>,
+    width: usize,
+}
+
+impl<'a> Grid<'a> {
+    /// Checks that every row has the same width.
+    pub fn new<I: IntoIterator<Item = &'a [u8]>>(rows: I) -> Result<Self, String> {
+        let rows = rows.into_iter().collect::<Vec<_>>();
+        let width = rows.first().map_or(0, |row| row.len());
+        match rows.iter().position(|row| row.len() != width) {
+            Some(y) => Err(format!("row {y} is {} wide, but row 0 is {width}", rows[y].len())),
+            None => Ok(Self { rows, width }),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// The byte at (`x`, `y`), if that's on the grid.
+    pub fn get(&self, x: usize, y: usize) -> Option<u8> {
+        self.rows.get(y)?.get(x).copied()
+    }
+
+    pub fn row(&self, y: usize) -> &'a [u8] {
+        self.rows[y]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+        self.rows.iter().copied()
+    }
 }
 
 /// Auto-expands to the day (as a [`u8`]) corresponding based on the current filename.
@@ -59,7 +160,7 @@ macro_rules! get_day {
             let ones = modpath[modpath.len() - 1];
 
             // if there is no tens place
-            if tens < 0x30 || tens > 0x39 {
+            if !(0x30..=0x39).contains(&tens) {
                 ones - 0x30
             } else {
                 (tens - 0x30) * 10 + (ones - 0x30)
@@ -80,8 +181,37 @@ macro_rules! testcase {
         fn $name() {
             log_init();
             let input = get_input(get_day!(), $inputfile);
-            let answer = $partfn(input$(, $partfnarg)*);
+            let answer = $partfn(&input$(, $partfnarg)*);
             assert_eq!(answer, $expected);
         }
     };
 }
+
+#[test]
+fn input_views() -> Result<(), String> {
+    let input = Input::from("ab\r\ncd\n\n\nef\n\ngh\n");
+    assert_eq!(input.lines().collect::<Vec<_>>(), ["ab", "cd", "", "", "ef", "", "gh"]);
+    assert_eq!(input.blocks().collect::<Vec<_>>(), [vec!["ab", "cd"], vec!["ef"], vec!["gh"]]);
+    assert!(input.grid().is_err());
+
+    let input = Input::from("#.\n.#");
+    let grid = input.grid()?;
+    assert_eq!((grid.width(), grid.height()), (2, 2));
+    assert_eq!(grid.get(1, 1), Some(b'#'));
+    assert_eq!(grid.get(2, 0), None);
+    assert_eq!(grid.row(1), b".#");
+    Ok(())
+}
+
+#[test]
+fn input_open() {
+    let path = std::env::temp_dir().join(format!("aoc2023-input-{}", std::process::id()));
+    std::fs::write(&path, "ab\ncd\n").unwrap();
+    let text = Input::open(&path);
+    std::fs::write(&path, b"ab\n\xff\n").unwrap();
+    let binary = Input::open(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(text.unwrap().lines().collect::<Vec<_>>(), ["ab", "cd"]);
+    assert_eq!(binary.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+}
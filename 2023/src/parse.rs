@@ -0,0 +1,118 @@
+//! Helpers shared by every day for picking puzzle inputs apart: regexes which are only compiled
+//! once, scanners which pull every integer out of a line, and a splitter for blank-line separated
+//! sections.
+
+use std::marker::PhantomData;
+
+pub use regex::Regex;
+
+/// Expands to a `&'static` [`Regex`] which is compiled the first time it's used, so it can sit in
+/// a loop (or a function called once per line) without being rebuilt each time.
+///
+/// Panics on first use if the pattern doesn't compile.
+#[macro_export]
+macro_rules! regex {
+    ($re:literal $(,)?) => {{
+        static RE: std::sync::OnceLock<$crate::parse::Regex> = std::sync::OnceLock::new();
+        RE.get_or_init(|| $crate::parse::compile($re))
+    }};
+}
+
+/// Compiles a pattern for [`regex!`].
+#[doc(hidden)]
+pub fn compile(re: &str) -> Regex {
+    Regex::new(re).unwrap_or_else(|e| panic!("bad regex: {e}"))
+}
+
+/// A primitive integer type which [`uints`] and [`ints`] can scan for.
+pub trait Integer: Copy {
+    /// Builds a number from its decimal `digits`, or `None` if it doesn't fit.
+    fn from_digits(digits: &[u8], negative: bool) -> Option<Self>;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {$(
+        impl Integer for $t {
+            fn from_digits(digits: &[u8], negative: bool) -> Option<Self> {
+                // build negative numbers downwards, so that MIN still fits
+                digits.iter().try_fold(0 as $t, |n, &d| {
+                    let n = n.checked_mul(10)?;
+                    match negative {
+                        true => n.checked_sub((d - b'0') as $t),
+                        false => n.checked_add((d - b'0') as $t),
+                    }
+                })
+            }
+        }
+    )*};
+}
+
+impl_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Every run of digits in some text, parsed as a `T`. Made by [`uints`] or [`ints`].
+pub struct Ints<'a, T> {
+    text: &'a str,
+    pos: usize,
+    signed: bool,
+    _int: PhantomData<T>,
+}
+
+impl<T: Integer> Iterator for Ints<'_, T> {
+    type Item = Result<T, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.text.as_bytes();
+        let start = self.pos + bytes[self.pos..].iter().position(u8::is_ascii_digit)?;
+        let len = bytes[start..].iter().take_while(|b| b.is_ascii_digit()).count();
+        self.pos = start + len;
+
+        let negative = self.signed && start > 0 && bytes[start - 1] == b'-';
+        let number = T::from_digits(&bytes[start..self.pos], negative);
+        let text = &self.text[start - negative as usize..self.pos];
+        Some(number.ok_or_else(|| format!("{text} doesn't fit in {}", std::any::type_name::<T>())))
+    }
+}
+
+/// Every unsigned integer in `line`, skipping over anything which isn't a digit (including signs).
+pub fn uints<T: Integer>(line: &str) -> Ints<'_, T> {
+    Ints { text: line, pos: 0, signed: false, _int: PhantomData }
+}
+
+/// Every integer in `line`, where a `-` right before some digits makes them negative (so `3-4`
+/// reads as 3 and -4).
+pub fn ints<T: Integer>(line: &str) -> Ints<'_, T> {
+    Ints { text: line, pos: 0, signed: true, _int: PhantomData }
+}
+
+/// Each section of `text` separated by blank lines, as its lines (leaving out any empty sections).
+pub fn sections(text: &str) -> impl Iterator<Item = Vec<&str>> {
+    let mut lines = text.lines().peekable();
+    std::iter::from_fn(move || {
+        while lines.next_if(|line| line.is_empty()).is_some() {}
+        lines.peek()?;
+        Some(lines.by_ref().take_while(|line| !line.is_empty()).collect())
+    })
+}
+
+#[test]
+fn scanners() {
+    let line = "Card  7: 41 -48 x=-3..4, 0";
+    assert_eq!(uints::<u32>(line).collect::<Result<Vec<_>, _>>(), Ok(vec![7, 41, 48, 3, 4, 0]));
+    assert_eq!(ints::<i64>(line).collect::<Result<Vec<_>, _>>(), Ok(vec![7, 41, -48, -3, 4, 0]));
+    assert_eq!(ints::<i8>("-128 127").collect::<Result<Vec<_>, _>>(), Ok(vec![-128, 127]));
+    assert_eq!(ints::<i8>("128").next(), Some(Err("128 doesn't fit in i8".into())));
+    assert_eq!(ints::<u8>("-1").next(), Some(Err("-1 doesn't fit in u8".into())));
+    assert_eq!(uints::<u128>("no numbers here").next(), None);
+
+    // each use of the macro compiles its regex just once
+    let header = || regex!("^([a-z]+)-to-([a-z]+) map:$");
+    assert!(std::ptr::eq(header(), header()));
+    assert_eq!(&header().captures("seed-to-soil map:").unwrap()[2], "soil");
+}
+
+#[test]
+fn section_splits() {
+    let text = "\n\nab\r\ncd\n\n\nef\n\ngh\n\n";
+    assert_eq!(sections(text).collect::<Vec<_>>(), [vec!["ab", "cd"], vec!["ef"], vec!["gh"]]);
+    assert_eq!(sections("").count(), 0);
+}
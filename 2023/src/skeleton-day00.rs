@@ -3,11 +3,11 @@
 use super::*;
 type AdvInt = usize;
 
-pub fn solve_part1<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
+pub fn solve_part1(input: &Input) -> AdvInt {
     todo!()
 }
 
-pub fn solve_part2<L: IntoIterator<Item = String>>(input: L) -> AdvInt {
+pub fn solve_part2(input: &Input) -> AdvInt {
     todo!()
 }
 